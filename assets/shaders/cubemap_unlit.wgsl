@@ -15,7 +15,9 @@ var base_color_sampler: sampler;
 fn fragment(
     #import bevy_pbr::mesh_vertex_output
 ) -> @location(0) vec4<f32> {
-    let fragment_position_view_lh = world_position.xyz * vec3<f32>(1.0, 1.0, -1.0);
+    // sample by direction from the camera so the sky doesn't slide when the world origin shifts
+    let direction = world_position.xyz - view.world_position;
+    let fragment_position_view_lh = direction * vec3<f32>(1.0, 1.0, -1.0);
     return textureSample(
         base_color_texture,
         base_color_sampler,
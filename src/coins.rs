@@ -0,0 +1,216 @@
+use crate::{
+    game_state::GameState,
+    lanes::LaneConfig,
+    obstacles::{Dodge, ObstacleRowEvent},
+    player::PlayerCollider,
+    score::RunStats,
+    seed::RunSeed,
+    track::{ChunkEntity, TrackPath, TrackPosition},
+};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub struct CoinPlugin;
+
+impl Plugin for CoinPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoinConfig>()
+            .add_event::<CoinCollectedEvent>()
+            .add_startup_system(setup)
+            .add_system(spawn_coins)
+            .add_system(spin_coins)
+            .add_systems((collect_coins, add_coins_to_run).in_set(OnUpdate(GameState::Playing)));
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct CoinConfig {
+    /// chance of an obstacle row getting a coin line
+    pub row_chance: f32,
+    pub coins_per_line: u32,
+    /// meters between coins in a line
+    pub spacing: f32,
+    /// height of coins the player collects by just running
+    pub height: f32,
+    /// peak height of coin arcs over obstacles the player has to jump
+    pub arc_height: f32,
+    /// height of coin trails under obstacles the player has to slide under
+    pub slide_height: f32,
+    pub value: u32,
+}
+
+impl Default for CoinConfig {
+    fn default() -> Self {
+        Self {
+            row_chance: 0.6,
+            coins_per_line: 5,
+            spacing: 3.0,
+            height: 1.5,
+            arc_height: 5.0,
+            slide_height: 1.0,
+            value: 1,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Coin {
+    pub value: u32,
+}
+
+#[derive(Component)]
+struct CoinMesh;
+
+pub struct CoinCollectedEvent {
+    pub value: u32,
+}
+
+#[derive(Resource)]
+struct CoinAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(CoinAssets {
+        mesh: meshes.add(Mesh::from(shape::Cylinder {
+            radius: 0.6,
+            height: 0.15,
+            ..default()
+        })),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 0.8, 0.1),
+            metallic: 1.0,
+            perceptual_roughness: 0.3,
+            ..default()
+        }),
+    });
+}
+
+// lay a line of coins through one of the lanes the player can make it through each obstacle row
+fn spawn_coins(
+    mut commands: Commands,
+    coin_assets: Res<CoinAssets>,
+    coin_config: Res<CoinConfig>,
+    lane_config: Res<LaneConfig>,
+    track_path: Res<TrackPath>,
+    run_seed: Res<RunSeed>,
+    mut obstacle_row_events: EventReader<ObstacleRowEvent>,
+) {
+    for row in obstacle_row_events.iter() {
+        // seeded per row so coins don't shift the obstacle layout for a given seed
+        let mut rng = StdRng::seed_from_u64(run_seed.seed ^ row.distance.to_bits() as u64);
+        if !rng.gen_bool(coin_config.row_chance.clamp(0.0, 1.0) as f64) {
+            continue;
+        }
+        let open_lanes: Vec<(usize, Dodge)> = row
+            .reachable
+            .iter()
+            .enumerate()
+            .filter_map(|(index, dodge)| dodge.map(|dodge| (index, dodge)))
+            .collect();
+        if open_lanes.is_empty() {
+            continue;
+        }
+        let (lane_index, dodge) = open_lanes[rng.gen_range(0..open_lanes.len())];
+        let lane_offset = lane_config.x(lane_index);
+
+        let count = coin_config.coins_per_line.max(1);
+        let half_length = (count - 1) as f32 * coin_config.spacing / 2.0;
+        for i in 0..count {
+            let offset = i as f32 * coin_config.spacing - half_length;
+            let (distance, height) = match dodge {
+                // lead up to the row
+                Dodge::Run => (row.distance - half_length + offset, coin_config.height),
+                // arc over the obstacle, peaking right above it
+                Dodge::Jump => {
+                    let t = if half_length > 0.0 {
+                        offset / half_length
+                    } else {
+                        0.0
+                    };
+                    (
+                        row.distance + offset,
+                        coin_config.height
+                            + (coin_config.arc_height - coin_config.height) * (1.0 - t * t),
+                    )
+                }
+                // trail under the obstacle
+                Dodge::Slide => (row.distance + offset, coin_config.slide_height),
+            };
+            let track_position = TrackPosition {
+                distance: distance as f64,
+                lane_offset,
+                height,
+            };
+            commands
+                .spawn((
+                    SpatialBundle::from_transform(track_path.transform(&track_position)),
+                    track_position,
+                    Coin {
+                        value: coin_config.value,
+                    },
+                    Collider::ball(0.8),
+                    Sensor,
+                    ChunkEntity { chunk: row.chunk },
+                    Name::new(format!("coin_{}_{}", distance as i32, lane_index)),
+                ))
+                .with_children(|coin| {
+                    // stand the coin up, the root follows the track so the mesh does the spinning
+                    coin.spawn((
+                        PbrBundle {
+                            mesh: coin_assets.mesh.clone(),
+                            material: coin_assets.material.clone(),
+                            transform: Transform::from_rotation(Quat::from_rotation_x(
+                                std::f32::consts::FRAC_PI_2,
+                            )),
+                            ..default()
+                        },
+                        CoinMesh,
+                    ));
+                });
+        }
+    }
+}
+
+fn spin_coins(mut coins: Query<&mut Transform, With<CoinMesh>>, time: Res<Time>) {
+    for mut transform in coins.iter_mut() {
+        transform.rotate_y(time.delta_seconds() * 3.0);
+    }
+}
+
+fn collect_coins(
+    mut commands: Commands,
+    query_player_collider: Query<Entity, With<PlayerCollider>>,
+    query_coins: Query<&Coin>,
+    mut contact_events: EventReader<CollisionEvent>,
+    mut coin_events: EventWriter<CoinCollectedEvent>,
+) {
+    for contact_event in contact_events.iter() {
+        for player_entity in query_player_collider.iter() {
+            if let CollisionEvent::Started(h1, h2, _event_flag) = contact_event {
+                if h1 == &player_entity || h2 == &player_entity {
+                    let other = if h1 == &player_entity { h2 } else { h1 };
+                    if let Ok(coin) = query_coins.get(*other) {
+                        coin_events.send(CoinCollectedEvent { value: coin.value });
+                        commands.entity(*other).despawn_recursive();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn add_coins_to_run(
+    mut run_stats: ResMut<RunStats>,
+    mut coin_events: EventReader<CoinCollectedEvent>,
+) {
+    for coin in coin_events.iter() {
+        run_stats.coins += coin.value;
+    }
+}
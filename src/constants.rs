@@ -1,3 +1,3 @@
-pub const TRACK_LENGTH: f32 = 1140.0;
-
 pub const LANE_FACTOR: f32 = 4.0;
+// track-wide models, the boardwalk and obstacles covering every lane, are built for three lanes
+pub const MODEL_LANES: f32 = 3.0;
@@ -1,17 +1,42 @@
-use crate::{constants::TRACK_LENGTH, environment::skybox::SkyboxPlugin}; // probably a better way to do this, in level for right now since nothing else needs to know about it yet
+use crate::{
+    environment::skybox::SkyboxPlugin, // probably a better way to do this, in level for right now since nothing else needs to know about it yet
+    game_state::RunAssets,
+    lanes::LaneConfig,
+    track::{ChunkEntity, SpawnChunkEvent, TrackPath, TrackPosition, TurnEvent},
+};
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
 
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup).add_plugin(SkyboxPlugin);
+        app.add_startup_system(setup)
+            .add_system(spawn_boardwalk_chunks)
+            .add_system(clear_junction_previews)
+            .add_plugin(SkyboxPlugin);
     }
 }
 
 const BOARDWALK_LENGTH: f32 = 42.0;
+// ground reaches this far past the outer lanes
+const BOARDWALK_MARGIN: f32 = 2.0;
+const GROUND_THICKNESS: f32 = 1.0;
+// boardwalks laid down each branch of a T before it is picked
+const PREVIEW_BOARDWALKS: u32 = 2;
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Boardwalk showing where a branch of a T goes, replaced by the real track once one is picked.
+#[derive(Component)]
+struct JunctionPreview;
+
+#[derive(Resource)]
+struct BoardwalkScene(Handle<Scene>);
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut run_assets: ResMut<RunAssets>,
+) {
     commands.spawn(DirectionalLightBundle {
         directional_light: DirectionalLight {
             illuminance: 25000.0,
@@ -22,28 +47,139 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             translation: Vec3::new(0.0, 0.0, 0.0),
             rotation: Quat::from_euler(
                 EulerRot::XYZ,
-                (-60.0 as f32).to_radians(),
-                (-30.0 as f32).to_radians(),
+                (-60.0_f32).to_radians(),
+                (-30.0_f32).to_radians(),
                 0.0,
             ),
             ..default()
         },
         ..default()
     });
-    for i in 0..((TRACK_LENGTH + 200.0) / BOARDWALK_LENGTH) as i32 {
-        let boardwalk_name = format!("boardwalk_{}", i);
-        commands.spawn((
-            // spawn a boardwalk for each boardwalk length
-            SceneBundle {
-                scene: asset_server.load("models/boardwalk/boardwalk.gltf#Scene0"),
-                transform: Transform::from_translation(Vec3::new(
-                    0.0,
-                    0.0,
-                    -i as f32 * BOARDWALK_LENGTH,
-                )),
+
+    let boardwalk_scene = asset_server.load("models/boardwalk/boardwalk.gltf#Scene0");
+    run_assets.0.push(boardwalk_scene.clone_untyped());
+    commands.insert_resource(BoardwalkScene(boardwalk_scene));
+}
+
+fn spawn_boardwalk_chunks(
+    mut commands: Commands,
+    boardwalk_scene: Res<BoardwalkScene>,
+    lane_config: Res<LaneConfig>,
+    track_path: Res<TrackPath>,
+    mut spawn_chunk_events: EventReader<SpawnChunkEvent>,
+) {
+    let x_scale = lane_config.model_scale();
+    // the ground is a child, so it gets stretched along with the model
+    let half_width = (lane_config.track_width() / 2.0 + BOARDWALK_MARGIN) / x_scale;
+    for chunk in spawn_chunk_events.iter() {
+        // a chunk starting on a junction starts where the corner the last chunk laid ends
+        let start = chunk.start
+            + track_path
+                .junction_at(chunk.start)
+                .map_or(0.0, |junction| junction.arc_length());
+        // fill the chunk with whole boardwalks, squashed a little to fit
+        let count = ((chunk.end - start) / BOARDWALK_LENGTH).ceil().max(1.0);
+        let length = (chunk.end - start) / count;
+        for i in 0..count as u32 {
+            let track_position = TrackPosition {
+                distance: (start + (i as f32 + 0.5) * length) as f64,
                 ..default()
-            },
-            Name::new(boardwalk_name),
-        ));
+            };
+            commands
+                .spawn((
+                    SceneBundle {
+                        scene: boardwalk_scene.0.clone(),
+                        transform: track_path.transform(&track_position).with_scale(Vec3::new(
+                            x_scale,
+                            1.0,
+                            length / BOARDWALK_LENGTH,
+                        )),
+                        ..default()
+                    },
+                    track_position,
+                    ChunkEntity { chunk: chunk.index },
+                    Name::new(format!("boardwalk_{}_{}", chunk.index, i)),
+                ))
+                .with_children(|boardwalk| spawn_ground(boardwalk, half_width));
+        }
+
+        let Some(junction) = track_path.junction_at(chunk.end) else {
+            continue;
+        };
+        // one square pad covers the corner whichever way it turns
+        let track_position = TrackPosition {
+            distance: junction.distance as f64,
+            ..default()
+        };
+        commands
+            .spawn((
+                SpatialBundle::from_transform(track_path.transform(&track_position)),
+                track_position,
+                ChunkEntity { chunk: chunk.index },
+                Name::new(format!("junction_{}", chunk.index)),
+            ))
+            .with_children(|corner| {
+                corner
+                    .spawn(SceneBundle {
+                        scene: boardwalk_scene.0.clone(),
+                        transform: Transform::from_xyz(0.0, 0.0, -junction.radius).with_scale(
+                            Vec3::new(x_scale, 1.0, junction.radius * 2.0 / BOARDWALK_LENGTH),
+                        ),
+                        ..default()
+                    })
+                    .with_children(|pad| spawn_ground(pad, junction.radius / x_scale));
+            });
+
+        // let the player see where each branch of a T goes before they pick one
+        if junction.awaiting_choice() {
+            for &turn in junction.branches.iter() {
+                let branch_path = track_path.with_turn(junction.distance, turn);
+                for i in 0..PREVIEW_BOARDWALKS {
+                    let track_position = TrackPosition {
+                        distance: (junction.distance
+                            + junction.arc_length()
+                            + (i as f32 + 0.5) * BOARDWALK_LENGTH)
+                            as f64,
+                        ..default()
+                    };
+                    commands.spawn((
+                        SceneBundle {
+                            scene: boardwalk_scene.0.clone(),
+                            transform: branch_path
+                                .transform(&track_position)
+                                .with_scale(Vec3::new(x_scale, 1.0, 1.0)),
+                            ..default()
+                        },
+                        JunctionPreview,
+                        ChunkEntity { chunk: chunk.index },
+                        Name::new(format!("junction_preview_{:?}_{}", turn, i)),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// solid ground for the player to land on, top face at the boardwalk surface. the boardwalk's
+// scale stretches it to the boardwalk's size
+fn spawn_ground(boardwalk: &mut ChildBuilder, half_width: f32) {
+    boardwalk.spawn((
+        TransformBundle::from(Transform::from_xyz(0.0, -GROUND_THICKNESS / 2.0, 0.0)),
+        Collider::cuboid(half_width, GROUND_THICKNESS / 2.0, BOARDWALK_LENGTH / 2.0),
+        Name::new("boardwalk ground"),
+    ));
+}
+
+fn clear_junction_previews(
+    mut commands: Commands,
+    mut turn_events: EventReader<TurnEvent>,
+    previews: Query<Entity, With<JunctionPreview>>,
+) {
+    if turn_events.iter().count() == 0 {
+        return;
+    }
+    // the picked branch streams in for real now
+    for entity in previews.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
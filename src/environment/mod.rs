@@ -1,2 +1,2 @@
 pub mod level;
-mod skybox;
+pub mod skybox;
@@ -18,17 +18,17 @@ use bevy::{
     },
 };
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, States)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default, States)]
 pub enum SkyboxState {
+    #[default]
     Loading,
     Loaded,
 }
 
-impl Default for SkyboxState {
-    fn default() -> Self {
-        Self::Loading
-    }
-}
+/// The sky cube. It stays centered on the camera instead of moving with the world, so it never
+/// gets any closer however far the player runs.
+#[derive(Component)]
+pub struct Skybox;
 
 #[derive(Resource)]
 pub struct Cubemap {
@@ -134,7 +134,8 @@ impl Plugin for SkyboxPlugin {
         app.add_state::<SkyboxState>()
             .add_plugin(MaterialPlugin::<CubemapMaterial>::default())
             .add_startup_system(setup_skybox)
-            .add_system(load_skybox);
+            .add_system(load_skybox)
+            .add_system(follow_camera);
     }
 }
 
@@ -146,6 +147,7 @@ fn setup_skybox(mut commands: Commands, asset_server: Res<AssetServer>) {
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn load_skybox(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -159,7 +161,7 @@ fn load_skybox(
     if !cubemap.is_loaded
         && asset_server.get_load_state(cubemap.image_handle.clone_weak()) == LoadState::Loaded
     {
-        let mut image = images.get_mut(&cubemap.image_handle).unwrap();
+        let image = images.get_mut(&cubemap.image_handle).unwrap();
         // NOTE: PNGs do not have any metadata that could indicate they contain a cubemap texture,
         // so they appear as one texture. The following code reconfigures the texture as necessary.
         if image.texture_descriptor.array_layer_count() == 1 {
@@ -190,6 +192,7 @@ fn load_skybox(
                     ..default()
                 },
                 Name::new("Skybox"),
+                Skybox,
             ));
         }
 
@@ -199,6 +202,18 @@ fn load_skybox(
     }
 }
 
+fn follow_camera(
+    camera: Query<&GlobalTransform, With<Camera3d>>,
+    mut skybox: Query<&mut Transform, With<Skybox>>,
+) {
+    let Ok(camera_transform) = camera.get_single() else {
+        return;
+    };
+    for mut transform in skybox.iter_mut() {
+        transform.translation = camera_transform.translation();
+    }
+}
+
 #[allow(dead_code)]
 pub fn set_skybox_texture(mut cubemap: ResMut<Cubemap>, image_handle: Handle<Image>) {
     cubemap.image_handle = image_handle;
@@ -0,0 +1,113 @@
+use crate::input::{ActionState, InputAction};
+use bevy::{asset::LoadState, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .init_resource::<RunAssets>()
+            .add_system(start_run.in_set(OnUpdate(GameState::MainMenu)))
+            // after every plugin had its chance to queue more run assets this frame
+            .add_system(
+                finish_loading
+                    .in_base_set(CoreSet::PostUpdate)
+                    .run_if(in_state(GameState::Loading)),
+            )
+            .add_system(pause_run.in_set(OnUpdate(GameState::Playing)))
+            .add_system(resume_run.in_set(OnUpdate(GameState::Paused)))
+            .init_resource::<GameOverDelay>()
+            .add_system(start_game_over_delay.in_schedule(OnEnter(GameState::GameOver)))
+            .add_system(leave_game_over.in_set(OnUpdate(GameState::GameOver)))
+            .add_system(pause_physics.in_schedule(OnEnter(GameState::Paused)))
+            .add_system(resume_physics.in_schedule(OnExit(GameState::Paused)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default, States)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Loading,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Handles that have to finish loading before a run can leave `GameState::Loading`.
+/// Plugins push their handles in here from their startup systems.
+#[derive(Resource, Default)]
+pub struct RunAssets(pub Vec<HandleUntyped>);
+
+fn start_run(action_state: Res<ActionState>, mut next_state: ResMut<NextState<GameState>>) {
+    if action_state.just_pressed(InputAction::Confirm) {
+        next_state.set(GameState::Loading);
+    }
+}
+
+fn finish_loading(
+    asset_server: Res<AssetServer>,
+    run_assets: Res<RunAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match asset_server.get_group_load_state(run_assets.0.iter().map(|handle| handle.id())) {
+        LoadState::Loaded => next_state.set(GameState::Playing),
+        LoadState::Failed => error!("failed to load run assets"),
+        _ => {}
+    }
+}
+
+fn pause_run(action_state: Res<ActionState>, mut next_state: ResMut<NextState<GameState>>) {
+    if action_state.just_pressed(InputAction::Pause) {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn resume_run(action_state: Res<ActionState>, mut next_state: ResMut<NextState<GameState>>) {
+    if action_state.just_pressed(InputAction::Pause) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// Seconds game over ignores input for, confirm shares its bindings with jump and a jump pressed
+/// as the run ends shouldn't skip straight into the next one.
+const GAME_OVER_DELAY: f32 = 0.75;
+
+#[derive(Resource)]
+struct GameOverDelay(Timer);
+
+impl Default for GameOverDelay {
+    fn default() -> Self {
+        Self(Timer::from_seconds(GAME_OVER_DELAY, TimerMode::Once))
+    }
+}
+
+fn start_game_over_delay(mut game_over_delay: ResMut<GameOverDelay>) {
+    game_over_delay.0.reset();
+}
+
+fn leave_game_over(
+    action_state: Res<ActionState>,
+    mut game_over_delay: ResMut<GameOverDelay>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    if !game_over_delay.0.tick(time.delta()).finished() {
+        return;
+    }
+    // restart straight away or go back to the menu
+    if action_state.just_pressed(InputAction::Confirm) {
+        next_state.set(GameState::Loading);
+    } else if action_state.just_pressed(InputAction::Pause) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn pause_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
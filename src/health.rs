@@ -0,0 +1,202 @@
+use crate::{
+    game_state::GameState,
+    input::{ActionState, InputAction},
+    obstacles::ObstacleType,
+    player::Player,
+};
+use bevy::prelude::*;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageConfig>()
+            .add_event::<PlayerHitEvent>()
+            .add_system(reset_health.in_schedule(OnEnter(GameState::Loading)))
+            .add_systems(
+                (tick_invulnerability, tick_staggering, log_player_hits)
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+        // a cheat for testing, shipped builds always play with the configured mode
+        if cfg!(debug_assertions) {
+            app.add_system(toggle_damage_mode.in_set(OnUpdate(GameState::MainMenu)));
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageMode {
+    /// every hit costs a life, the run only ends when the last one is gone
+    Forgiving,
+    /// the first hit ends the run
+    OneHit,
+}
+
+/// Designer facing settings for how punishing obstacle hits are.
+#[derive(Resource, Clone, Debug)]
+pub struct DamageConfig {
+    pub mode: DamageMode,
+    pub lives: u32,
+    /// seconds the player can't be hit again after a stumble
+    pub invulnerability_duration: f32,
+    /// seconds between visibility toggles while invulnerable
+    pub blink_interval: f32,
+    /// seconds the stumble after a side hit lasts
+    pub stagger_duration: f32,
+    /// how far the model lurches back at the height of the stumble, in radians
+    pub stagger_pitch: f32,
+}
+
+impl Default for DamageConfig {
+    fn default() -> Self {
+        Self {
+            mode: DamageMode::Forgiving,
+            lives: 3,
+            invulnerability_duration: 1.5,
+            blink_interval: 0.1,
+            stagger_duration: 0.5,
+            stagger_pitch: 0.35,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitOutcome {
+    /// the player lost a life but keeps running
+    Stumble,
+    /// the run is over
+    Fatal,
+    /// clipped the side of an obstacle changing lanes and got pushed back, no life lost
+    SideHit,
+}
+
+pub struct PlayerHitEvent {
+    pub outcome: HitOutcome,
+    pub obstacle_type: ObstacleType,
+    pub lives_left: u32,
+}
+
+#[derive(Component, Debug)]
+pub struct Health {
+    pub lives: u32,
+}
+
+impl Health {
+    pub fn take_hit(&mut self, mode: DamageMode) -> HitOutcome {
+        self.lives = match mode {
+            DamageMode::Forgiving => self.lives.saturating_sub(1),
+            DamageMode::OneHit => 0,
+        };
+        if self.lives == 0 {
+            HitOutcome::Fatal
+        } else {
+            HitOutcome::Stumble
+        }
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            lives: DamageConfig::default().lives,
+        }
+    }
+}
+
+/// Obstacle hits are ignored while this is on the player.
+#[derive(Component)]
+pub struct Invulnerable {
+    pub timer: Timer,
+}
+
+impl Invulnerable {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+/// The player bounced off the side of an obstacle and is stumbling back into their lane.
+#[derive(Component)]
+pub struct Staggering {
+    pub timer: Timer,
+}
+
+impl Staggering {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+fn toggle_damage_mode(action_state: Res<ActionState>, mut damage_config: ResMut<DamageConfig>) {
+    if action_state.just_pressed(InputAction::ToggleDamageMode) {
+        damage_config.mode = match damage_config.mode {
+            DamageMode::Forgiving => DamageMode::OneHit,
+            DamageMode::OneHit => DamageMode::Forgiving,
+        };
+        info!("damage mode: {:?}", damage_config.mode);
+    }
+}
+
+fn reset_health(
+    mut commands: Commands,
+    damage_config: Res<DamageConfig>,
+    mut player: Query<(Entity, &mut Health, &mut Visibility), With<Player>>,
+) {
+    for (entity, mut health, mut visibility) in player.iter_mut() {
+        health.lives = damage_config.lives;
+        *visibility = Visibility::Inherited;
+        commands
+            .entity(entity)
+            .remove::<Invulnerable>()
+            .remove::<Staggering>();
+    }
+}
+
+// blink the player model until the invulnerability window runs out
+fn tick_invulnerability(
+    mut commands: Commands,
+    damage_config: Res<DamageConfig>,
+    mut player: Query<(Entity, &mut Invulnerable, &mut Visibility), With<Player>>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable, mut visibility) in player.iter_mut() {
+        invulnerable.timer.tick(time.delta());
+        if invulnerable.timer.finished() {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Invulnerable>();
+            continue;
+        }
+        let blinks = (invulnerable.timer.elapsed_secs() / damage_config.blink_interval) as u32;
+        *visibility = if blinks.is_multiple_of(2) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn tick_staggering(
+    mut commands: Commands,
+    mut player: Query<(Entity, &mut Staggering), With<Player>>,
+    time: Res<Time>,
+) {
+    for (entity, mut staggering) in player.iter_mut() {
+        staggering.timer.tick(time.delta());
+        if staggering.timer.finished() {
+            commands.entity(entity).remove::<Staggering>();
+        }
+    }
+}
+
+fn log_player_hits(mut hit_events: EventReader<PlayerHitEvent>) {
+    for hit in hit_events.iter() {
+        info!(
+            "hit a {:?} obstacle, {:?} with {} lives left",
+            hit.obstacle_type, hit.outcome, hit.lives_left
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::InputAction;
+
+/// Where the bindings are saved, relative to the working directory.
+const BINDINGS_PATH: &str = "config/bindings.ron";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// a stick or trigger pushed past the threshold in one direction
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub actions: BTreeMap<InputAction, Vec<Binding>>,
+    /// how far a stick has to be pushed before it counts as pressed
+    pub stick_threshold: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Binding::*;
+        let actions = BTreeMap::from([
+            (
+                InputAction::MoveLeft,
+                vec![
+                    Key(KeyCode::A),
+                    Key(KeyCode::Left),
+                    GamepadButton(GamepadButtonType::DPadLeft),
+                    GamepadAxis {
+                        axis: GamepadAxisType::LeftStickX,
+                        positive: false,
+                    },
+                ],
+            ),
+            (
+                InputAction::MoveRight,
+                vec![
+                    Key(KeyCode::D),
+                    Key(KeyCode::Right),
+                    GamepadButton(GamepadButtonType::DPadRight),
+                    GamepadAxis {
+                        axis: GamepadAxisType::LeftStickX,
+                        positive: true,
+                    },
+                ],
+            ),
+            (
+                InputAction::Jump,
+                vec![
+                    Key(KeyCode::W),
+                    Key(KeyCode::Up),
+                    GamepadButton(GamepadButtonType::South),
+                    GamepadButton(GamepadButtonType::DPadUp),
+                    GamepadAxis {
+                        axis: GamepadAxisType::LeftStickY,
+                        positive: true,
+                    },
+                ],
+            ),
+            (
+                InputAction::Slide,
+                vec![
+                    Key(KeyCode::S),
+                    Key(KeyCode::Down),
+                    GamepadButton(GamepadButtonType::East),
+                    GamepadButton(GamepadButtonType::DPadDown),
+                    GamepadAxis {
+                        axis: GamepadAxisType::LeftStickY,
+                        positive: false,
+                    },
+                ],
+            ),
+            (
+                InputAction::TurnLeft,
+                vec![
+                    Key(KeyCode::Q),
+                    GamepadButton(GamepadButtonType::LeftTrigger),
+                ],
+            ),
+            (
+                InputAction::TurnRight,
+                vec![
+                    Key(KeyCode::E),
+                    GamepadButton(GamepadButtonType::RightTrigger),
+                ],
+            ),
+            (
+                InputAction::Pause,
+                vec![
+                    Key(KeyCode::Escape),
+                    GamepadButton(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                InputAction::Confirm,
+                vec![
+                    Key(KeyCode::Return),
+                    Key(KeyCode::Space),
+                    GamepadButton(GamepadButtonType::South),
+                ],
+            ),
+            (InputAction::ToggleDamageMode, vec![Key(KeyCode::H)]),
+        ]);
+        Self {
+            actions,
+            stick_threshold: 0.5,
+        }
+    }
+}
+
+impl InputBindings {
+    /// Load the saved bindings, falling back to the defaults if there are none or they can't be read.
+    pub fn load_or_default() -> Self {
+        let mut bindings: Self = match fs::read_to_string(BINDINGS_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("ignoring invalid {}: {}", BINDINGS_PATH, err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        // actions added since the file was saved get their default bindings
+        for (action, defaults) in Self::default().actions {
+            bindings.actions.entry(action).or_insert(defaults);
+        }
+        bindings
+    }
+
+    /// Replace every binding of an action.
+    #[allow(dead_code)]
+    pub fn rebind(&mut self, action: InputAction, bindings: Vec<Binding>) {
+        self.actions.insert(action, bindings);
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = Path::new(BINDINGS_PATH).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(BINDINGS_PATH, contents)?;
+        Ok(())
+    }
+}
+
+// also runs on the first frame, so the file with the defaults is there to edit
+pub(super) fn save_bindings(input_bindings: Res<InputBindings>) {
+    if let Err(err) = input_bindings.save() {
+        warn!("failed to save {}: {}", BINDINGS_PATH, err);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::{ActionState, InputAction};
+
+/// Thresholds for turning touch and mouse drags into actions, in logical pixels.
+#[derive(Resource, Clone, Debug)]
+pub struct GestureConfig {
+    /// how far a drag has to travel before it counts as a swipe
+    pub min_distance: f32,
+    /// average pixels per second a drag needs to count as a swipe, slower drags are ignored
+    pub min_velocity: f32,
+    /// presses that move less than this and let go quickly are taps
+    pub max_tap_distance: f32,
+    pub max_tap_duration: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            min_distance: 40.0,
+            min_velocity: 200.0,
+            max_tap_distance: 10.0,
+            max_tap_duration: 0.25,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum StrokeSource {
+    Mouse,
+    Touch(u64),
+}
+
+struct Stroke {
+    start: Vec2,
+    start_time: f32,
+    /// a stroke only ever fires one swipe
+    fired: bool,
+}
+
+#[derive(Resource, Default)]
+pub(super) struct GestureTracker {
+    strokes: HashMap<StrokeSource, Stroke>,
+}
+
+impl GestureTracker {
+    fn begin(&mut self, source: StrokeSource, position: Vec2, time: f32) {
+        self.strokes.insert(
+            source,
+            Stroke {
+                start: position,
+                start_time: time,
+                fired: false,
+            },
+        );
+    }
+
+    // positions are y up, swipes fire as soon as they pass the thresholds rather than on release
+    fn drag(
+        &mut self,
+        source: StrokeSource,
+        position: Vec2,
+        time: f32,
+        config: &GestureConfig,
+    ) -> Option<InputAction> {
+        let stroke = self.strokes.get_mut(&source)?;
+        if stroke.fired {
+            return None;
+        }
+        let delta = position - stroke.start;
+        let elapsed = (time - stroke.start_time).max(f32::EPSILON);
+        if delta.length() < config.min_distance || delta.length() / elapsed < config.min_velocity {
+            return None;
+        }
+        stroke.fired = true;
+        Some(if delta.x.abs() > delta.y.abs() {
+            if delta.x > 0.0 {
+                InputAction::MoveRight
+            } else {
+                InputAction::MoveLeft
+            }
+        } else if delta.y > 0.0 {
+            InputAction::Jump
+        } else {
+            InputAction::Slide
+        })
+    }
+
+    fn end(
+        &mut self,
+        source: StrokeSource,
+        position: Vec2,
+        time: f32,
+        config: &GestureConfig,
+    ) -> Option<InputAction> {
+        let stroke = self.strokes.remove(&source)?;
+        let tapped = !stroke.fired
+            && position.distance(stroke.start) <= config.max_tap_distance
+            && time - stroke.start_time <= config.max_tap_duration;
+        tapped.then_some(InputAction::Confirm)
+    }
+}
+
+pub(super) fn recognize_gestures(
+    mut action_state: ResMut<ActionState>,
+    mut tracker: ResMut<GestureTracker>,
+    gesture_config: Res<GestureConfig>,
+    touches: Res<Touches>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    let mut actions = Vec::new();
+
+    // touch positions are y down, flip them to match the cursor
+    let flip = |position: Vec2| Vec2::new(position.x, -position.y);
+    for touch in touches.iter_just_pressed() {
+        tracker.begin(StrokeSource::Touch(touch.id()), flip(touch.position()), now);
+    }
+    for touch in touches.iter() {
+        let source = StrokeSource::Touch(touch.id());
+        actions.extend(tracker.drag(source, flip(touch.position()), now, &gesture_config));
+    }
+    for touch in touches
+        .iter_just_released()
+        .chain(touches.iter_just_cancelled())
+    {
+        let source = StrokeSource::Touch(touch.id());
+        actions.extend(tracker.end(source, flip(touch.position()), now, &gesture_config));
+    }
+
+    if let Some(cursor) = primary_window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    {
+        if mouse_buttons.just_pressed(MouseButton::Left) {
+            tracker.begin(StrokeSource::Mouse, cursor, now);
+        }
+        if mouse_buttons.pressed(MouseButton::Left) {
+            actions.extend(tracker.drag(StrokeSource::Mouse, cursor, now, &gesture_config));
+        }
+        if mouse_buttons.just_released(MouseButton::Left) {
+            actions.extend(tracker.end(StrokeSource::Mouse, cursor, now, &gesture_config));
+        }
+    } else if mouse_buttons.just_released(MouseButton::Left) {
+        // let go outside the window
+        tracker.strokes.remove(&StrokeSource::Mouse);
+    }
+
+    for action in actions {
+        action_state.press(action);
+    }
+}
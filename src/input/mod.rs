@@ -0,0 +1,115 @@
+mod bindings;
+mod gesture;
+
+use std::collections::HashSet;
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub use self::bindings::{Binding, InputBindings};
+pub use self::gesture::GestureConfig;
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBindings::load_or_default())
+            .init_resource::<ActionState>()
+            .init_resource::<GestureConfig>()
+            .init_resource::<gesture::GestureTracker>()
+            .add_systems(
+                (update_action_state, gesture::recognize_gestures)
+                    .chain()
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem),
+            )
+            .add_system(bindings::save_bindings.run_if(resource_changed::<InputBindings>()));
+    }
+}
+
+/// Everything the player can ask the game to do, independent of the device they asked with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Slide,
+    /// take a corner or pick a branch at a junction
+    TurnLeft,
+    TurnRight,
+    Pause,
+    /// start a run from the menus
+    Confirm,
+    /// switch between forgiving and one hit damage from the menu, debug builds only
+    ToggleDamageMode,
+}
+
+/// Actions held down and newly pressed this frame, rebuilt from the bindings every frame.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Trigger an action for this frame from something other than the bindings.
+    pub fn press(&mut self, action: InputAction) {
+        self.pressed.insert(action);
+        self.just_pressed.insert(action);
+    }
+}
+
+fn update_action_state(
+    mut action_state: ResMut<ActionState>,
+    input_bindings: Res<InputBindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+) {
+    let was_pressed = std::mem::take(&mut action_state.pressed);
+    action_state.just_pressed.clear();
+
+    for (action, bindings) in input_bindings.actions.iter() {
+        for binding in bindings {
+            let (pressed, just_pressed) = match *binding {
+                Binding::Key(key) => (
+                    keyboard_input.pressed(key),
+                    keyboard_input.just_pressed(key),
+                ),
+                Binding::GamepadButton(button_type) => {
+                    gamepads
+                        .iter()
+                        .fold((false, false), |(pressed, just_pressed), gamepad| {
+                            let button = GamepadButton::new(gamepad, button_type);
+                            (
+                                pressed || gamepad_buttons.pressed(button),
+                                just_pressed || gamepad_buttons.just_pressed(button),
+                            )
+                        })
+                }
+                Binding::GamepadAxis { axis, positive } => {
+                    // sticks count as pressed past the threshold, just pressed on the frame they cross it
+                    let pressed = gamepads.iter().any(|gamepad| {
+                        let value = gamepad_axes
+                            .get(GamepadAxis::new(gamepad, axis))
+                            .unwrap_or(0.0);
+                        let value = if positive { value } else { -value };
+                        value > input_bindings.stick_threshold
+                    });
+                    (pressed, pressed && !was_pressed.contains(action))
+                }
+            };
+            if pressed {
+                action_state.pressed.insert(*action);
+            }
+            if just_pressed {
+                action_state.just_pressed.insert(*action);
+            }
+        }
+    }
+}
@@ -1,37 +1,199 @@
-use crate::clamp::Clamp;
+use std::env;
+
+use crate::{
+    clamp::Clamp,
+    constants::{LANE_FACTOR, MODEL_LANES},
+};
 use bevy::prelude::*;
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum Lane {
-    Left = -1,
-    Middle = 0,
-    Right = 1,
+pub struct LanePlugin;
+
+impl Plugin for LanePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LaneConfig::from_args())
+            .init_resource::<LaneChangeConfig>();
+    }
+}
+
+pub const MIN_LANES: usize = 2;
+pub const MAX_LANES: usize = 7;
+
+/// How many lanes the track has and how far apart they are, lanes are indexed from the left.
+#[derive(Resource, Clone, Debug)]
+pub struct LaneConfig {
+    pub count: usize,
+    /// distance between the centers of neighbouring lanes
+    pub width: f32,
+}
+
+impl Default for LaneConfig {
+    fn default() -> Self {
+        Self {
+            count: 3,
+            width: LANE_FACTOR,
+        }
+    }
+}
+
+impl LaneConfig {
+    /// Reads `--lanes <n>` from the command line, falling back to the `GOON_LANES` environment variable.
+    fn from_args() -> Self {
+        let args: Vec<String> = env::args().collect();
+        let count = args
+            .iter()
+            .position(|arg| arg == "--lanes")
+            .and_then(|i| args.get(i + 1).cloned())
+            .or_else(|| env::var("GOON_LANES").ok())
+            .and_then(|count| match count.parse::<usize>() {
+                Ok(count) if (MIN_LANES..=MAX_LANES).contains(&count) => Some(count),
+                _ => {
+                    warn!(
+                        "ignoring lane count {:?}, it has to be {} to {}",
+                        count, MIN_LANES, MAX_LANES
+                    );
+                    None
+                }
+            });
+        match count {
+            Some(count) => Self { count, ..default() },
+            None => Self::default(),
+        }
+    }
+
+    /// The lane runs start in, right of center when the count is even.
+    pub fn middle(&self) -> usize {
+        self.count / 2
+    }
+
+    /// x position of a lane's center, the track is centered on x = 0.
+    pub fn x(&self, lane: usize) -> f32 {
+        (lane as f32 - (self.count - 1) as f32 / 2.0) * self.width
+    }
+
+    /// The lane whose center is closest to `x`.
+    pub fn nearest(&self, x: f32) -> usize {
+        let lane = (x / self.width + (self.count - 1) as f32 / 2.0).round() as i32;
+        Clamp::clamp(lane, 0, self.count as i32 - 1) as usize
+    }
+
+    /// Lane `offset` lanes away from the middle one, if the track is wide enough to have it.
+    pub fn offset_from_middle(&self, offset: i32) -> Option<usize> {
+        let lane = self.middle() as i32 + offset;
+        (0..self.count as i32)
+            .contains(&lane)
+            .then_some(lane as usize)
+    }
+
+    pub fn track_width(&self) -> f32 {
+        self.count as f32 * self.width
+    }
+
+    /// x scale that stretches a track-wide model across every lane.
+    pub fn model_scale(&self) -> f32 {
+        self.track_width() / (MODEL_LANES * LANE_FACTOR)
+    }
 }
 
 #[derive(Component)]
 pub struct LaneEntity {
-    pub lane: Lane,
+    /// index into the lanes of the `LaneConfig`
+    pub lane: usize,
 }
 
 impl LaneEntity {
-    pub fn change_lane(&mut self, direction: i32) {
+    pub fn change_lane(&mut self, direction: i32, lane_config: &LaneConfig) {
         // set the lane to the clamp of the current lane + the input
         let next_lane = Clamp::clamp(
             self.lane as i32 + direction,
-            Lane::Left as i32,
-            Lane::Right as i32,
+            0,
+            lane_config.count as i32 - 1,
         );
-        self.lane = match next_lane {
-            0 => Lane::Middle,
-            1 => Lane::Right,
-            -1 => Lane::Left,
-            _ => panic!("Invalid lane value"),
-        };
+        self.lane = next_lane as usize;
     }
 }
 
-impl Default for LaneEntity {
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseOutQuad,
+    EaseInOutCubic,
+}
+
+impl Easing {
+    /// Map linear progress in 0..=1 onto the eased curve.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct LaneChangeConfig {
+    /// seconds to slide over into the next lane
+    pub duration: f32,
+    pub easing: Easing,
+    /// how far the model rolls into a lane change at its peak, in radians
+    pub max_lean: f32,
+}
+
+impl Default for LaneChangeConfig {
     fn default() -> Self {
-        Self { lane: Lane::Middle }
+        Self {
+            duration: 0.2,
+            easing: Easing::EaseInOutCubic,
+            max_lean: 15.0_f32.to_radians(),
+        }
+    }
+}
+
+/// In-progress move between two lane x positions.
+#[derive(Component, Default, Debug)]
+pub struct LaneTween {
+    pub from: f32,
+    pub to: f32,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+impl LaneTween {
+    /// Already settled at `x`.
+    pub fn at(x: f32) -> Self {
+        Self {
+            from: x,
+            to: x,
+            ..default()
+        }
+    }
+
+    pub fn start(&mut self, from: f32, to: f32, duration: f32) {
+        *self = Self {
+            from,
+            to,
+            elapsed: 0.0,
+            duration,
+        };
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.duration).min(1.0)
+        }
+    }
+
+    /// x position along the tween with the easing applied
+    pub fn x(&self, easing: Easing) -> f32 {
+        self.from + (self.to - self.from) * easing.apply(self.progress())
     }
 }
@@ -3,18 +3,36 @@
 //!
 
 mod clamp;
+mod coins;
 mod constants;
 mod environment;
+mod game_state;
+mod health;
+mod input;
 mod lanes;
 mod obstacles;
 mod player;
+mod ron_loader;
+mod score;
+mod seed;
+mod speed;
+mod track;
 
 use crate::environment::level::LevelPlugin;
 use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
 use bevy_editor_pls::prelude::*;
 use bevy_rapier3d::prelude::*;
+use coins::CoinPlugin;
+use game_state::GameStatePlugin;
+use health::HealthPlugin;
+use input::InputPlugin;
+use lanes::LanePlugin;
 use obstacles::ObstaclePlugin;
 use player::PlayerPlugin;
+use score::ScorePlugin;
+use seed::SeedPlugin;
+use speed::SpeedPlugin;
+use track::TrackPlugin;
 
 fn main() {
     App::new()
@@ -22,12 +40,21 @@ fn main() {
             watch_for_changes: true,
             ..Default::default()
         }))
+        .add_plugin(InputPlugin)
+        .add_plugin(GameStatePlugin)
+        .add_plugin(SeedPlugin)
+        .add_plugin(LanePlugin)
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugin(EditorPlugin)
         .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(TrackPlugin)
         .add_plugin(LevelPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(RapierDebugRenderPlugin::default()) // disable hdr to use
         .add_plugin(ObstaclePlugin)
+        .add_plugin(CoinPlugin)
+        .add_plugin(ScorePlugin)
+        .add_plugin(HealthPlugin)
+        .add_plugin(SpeedPlugin)
         .run();
 }
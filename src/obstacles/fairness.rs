@@ -0,0 +1,238 @@
+use crate::{lanes::LaneChangeConfig, player::JumpConfig};
+
+use super::{manifest::LaneSpan, ObstacleResource, ObstacleType, PlannedRow};
+
+/// Rows rerolled before a row gets repaired instead.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// What the player can do to get through obstacles, used to make sure every generated row can be survived.
+#[derive(Clone, Debug)]
+pub struct MoveModel {
+    /// seconds to move over by one lane
+    pub lane_change_time: f32,
+    /// seconds from leaving the ground until a new jump or slide can start
+    pub jump_duration: f32,
+    pub slide_duration: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dodge {
+    Run,
+    Jump,
+    Slide,
+}
+
+impl Dodge {
+    fn for_obstacle(obstacle_type: ObstacleType) -> Option<Self> {
+        match obstacle_type {
+            ObstacleType::Low => Some(Dodge::Jump),
+            ObstacleType::High => Some(Dodge::Slide),
+            ObstacleType::Full => None,
+        }
+    }
+
+    /// Merge two obstacles sharing a lane, `None` once nothing gets through both.
+    fn combine(self, other: Dodge) -> Option<Self> {
+        match (self, other) {
+            (Dodge::Run, dodge) | (dodge, Dodge::Run) => Some(dodge),
+            (a, b) if a == b => Some(a),
+            _ => None,
+        }
+    }
+}
+
+/// The dodge that gets the player through each lane, indexed by lane from the left.
+/// `None` lanes are blocked, or unreachable when used for the reachable set.
+pub type LaneDodges = Vec<Option<Dodge>>;
+
+impl MoveModel {
+    /// The moves the player actually has, so tuning them can't make the check drift from the game.
+    pub fn new(lane_change_config: &LaneChangeConfig, jump_config: &JumpConfig) -> Self {
+        Self {
+            lane_change_time: lane_change_config.duration,
+            jump_duration: jump_config.air_time(),
+            slide_duration: jump_config.slide_duration,
+        }
+    }
+
+    fn duration(&self, dodge: Dodge) -> f32 {
+        match dodge {
+            Dodge::Run => 0.0,
+            Dodge::Jump => self.jump_duration,
+            Dodge::Slide => self.slide_duration,
+        }
+    }
+
+    /// Lanes of the next row the player can make it through, given the lanes they could be in at the
+    /// previous row and the seconds between the two.
+    pub fn reachable(&self, from: &LaneDodges, to: &LaneDodges, time: f32) -> LaneDodges {
+        let mut reachable = vec![None; to.len()];
+        for (to_index, to_dodge) in to.iter().enumerate() {
+            let Some(to_dodge) = to_dodge else {
+                continue;
+            };
+            let can_reach = from.iter().enumerate().any(|(from_index, from_dodge)| {
+                let Some(from_dodge) = from_dodge else {
+                    return false;
+                };
+                let lanes = (to_index as f32 - from_index as f32).abs();
+                // lane changes can happen mid jump or slide, a new jump or slide has to wait for the last
+                let recovered = *to_dodge == Dodge::Run || time >= self.duration(*from_dodge);
+                lanes * self.lane_change_time <= time && recovered
+            });
+            if can_reach {
+                reachable[to_index] = Some(*to_dodge);
+            }
+        }
+        reachable
+    }
+}
+
+pub fn row_dodges(
+    row: &PlannedRow,
+    obstacle_resources: &[ObstacleResource],
+    lane_count: usize,
+) -> LaneDodges {
+    let mut dodges = vec![Some(Dodge::Run); lane_count];
+    for planned in &row.obstacles {
+        let obstacle_resource = &obstacle_resources[planned.resource];
+        let lanes = match obstacle_resource.lane_span {
+            LaneSpan::Single => planned.lane..planned.lane + 1,
+            LaneSpan::All => 0..lane_count,
+        };
+        let dodge = Dodge::for_obstacle(obstacle_resource.obstacle_type);
+        for lane in lanes {
+            dodges[lane] = match (dodges[lane], dodge) {
+                (Some(a), Some(b)) => a.combine(b),
+                _ => None,
+            };
+        }
+    }
+    dodges
+}
+
+pub fn any_reachable(dodges: &LaneDodges) -> bool {
+    dodges.iter().any(|dodge| dodge.is_some())
+}
+
+/// Clear the obstacles in a lane the player can already be in, so they can stay put and run through.
+pub fn repair_row(
+    row: &mut PlannedRow,
+    from: &LaneDodges,
+    obstacle_resources: &[ObstacleResource],
+) {
+    let Some(open_lane) = from.iter().position(|dodge| dodge.is_some()) else {
+        return;
+    };
+    row.obstacles.retain(
+        |planned| match obstacle_resources[planned.resource].lane_span {
+            LaneSpan::Single => planned.lane != open_lane,
+            LaneSpan::All => false,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obstacles::{manifest::ColliderShape, PlannedObstacle};
+    use bevy::prelude::*;
+
+    const RUN: Option<Dodge> = Some(Dodge::Run);
+    const JUMP: Option<Dodge> = Some(Dodge::Jump);
+    const SLIDE: Option<Dodge> = Some(Dodge::Slide);
+
+    fn move_model() -> MoveModel {
+        MoveModel {
+            lane_change_time: 0.2,
+            jump_duration: 0.9,
+            slide_duration: 0.9,
+        }
+    }
+
+    fn obstacle_resource(obstacle_type: ObstacleType, lane_span: LaneSpan) -> ObstacleResource {
+        ObstacleResource {
+            obstacle_type,
+            scene_handle: Handle::default(),
+            collider: ColliderShape::Ball { radius: 1.0 },
+            collision_offset: Vec3::ZERO,
+            lane_span,
+            spawn_weight: 1.0,
+            min_count: 1,
+            max_count: 1,
+        }
+    }
+
+    fn row(obstacles: &[(usize, usize)]) -> PlannedRow {
+        PlannedRow {
+            distance: 0.0,
+            obstacles: obstacles
+                .iter()
+                .map(|&(resource, lane)| PlannedObstacle { resource, lane })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn lane_changes_take_time() {
+        let model = move_model();
+        let from = vec![RUN, None, None];
+        let to = vec![RUN; 3];
+        assert_eq!(model.reachable(&from, &to, 0.1), vec![RUN, None, None]);
+        assert_eq!(model.reachable(&from, &to, 0.2), vec![RUN, RUN, None]);
+        assert_eq!(model.reachable(&from, &to, 0.4), vec![RUN, RUN, RUN]);
+    }
+
+    #[test]
+    fn blocked_lanes_stay_unreachable() {
+        let model = move_model();
+        let from = vec![RUN; 3];
+        let to = vec![None, JUMP, None];
+        assert_eq!(model.reachable(&from, &to, 1.0), vec![None, JUMP, None]);
+        assert!(!any_reachable(&model.reachable(&from, &vec![None; 3], 1.0)));
+    }
+
+    #[test]
+    fn a_new_jump_or_slide_waits_for_the_last_one() {
+        let model = move_model();
+        let from = vec![JUMP];
+        assert_eq!(model.reachable(&from, &vec![SLIDE], 0.5), vec![None]);
+        assert_eq!(model.reachable(&from, &vec![SLIDE], 0.9), vec![SLIDE]);
+        // running on through doesn't have to wait
+        assert_eq!(model.reachable(&from, &vec![RUN], 0.5), vec![RUN]);
+    }
+
+    #[test]
+    fn stacked_obstacles_combine() {
+        let resources = vec![
+            obstacle_resource(ObstacleType::Low, LaneSpan::Single),
+            obstacle_resource(ObstacleType::High, LaneSpan::Single),
+            obstacle_resource(ObstacleType::Low, LaneSpan::All),
+        ];
+        assert_eq!(
+            row_dodges(&row(&[(0, 0), (2, 0)]), &resources, 2),
+            vec![JUMP, JUMP]
+        );
+        assert_eq!(
+            row_dodges(&row(&[(1, 0), (2, 0)]), &resources, 2),
+            vec![None, JUMP]
+        );
+    }
+
+    #[test]
+    fn repair_clears_a_lane_the_player_can_be_in() {
+        let model = move_model();
+        let resources = vec![
+            obstacle_resource(ObstacleType::Full, LaneSpan::Single),
+            obstacle_resource(ObstacleType::Full, LaneSpan::All),
+        ];
+        let from = vec![None, None, RUN];
+        let mut blocked = row(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        let dodges = row_dodges(&blocked, &resources, 3);
+        assert!(!any_reachable(&model.reachable(&from, &dodges, 0.0)));
+
+        repair_row(&mut blocked, &from, &resources);
+        let dodges = row_dodges(&blocked, &resources, 3);
+        assert_eq!(model.reachable(&from, &dodges, 0.0), vec![None, None, RUN]);
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::Collider;
+use serde::Deserialize;
+
+use super::ObstacleType;
+
+/// Every obstacle the generator can place, loaded from a `*.obstacles.ron` file.
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "4f0e2c8a-6b1d-4f57-9a3e-2d7c5b8e1f60"]
+pub struct ObstacleManifest {
+    pub obstacles: Vec<ObstacleDefinition>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ObstacleDefinition {
+    /// scene path relative to assets/, e.g. "models/obstacles/low/spike.gltf#Scene0"
+    pub model: String,
+    pub obstacle_type: ObstacleType,
+    pub collider: ColliderShape,
+    /// collider position relative to the obstacle origin
+    pub offset: (f32, f32, f32),
+    pub lane_span: LaneSpan,
+    /// relative chance of this obstacle being picked for a row
+    pub spawn_weight: f32,
+    /// how many lanes of a row this obstacle fills
+    pub min_count: u32,
+    pub max_count: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum ColliderShape {
+    Cuboid { half_extents: (f32, f32, f32) },
+    Ball { radius: f32 },
+    Capsule { half_height: f32, radius: f32 },
+}
+
+impl ColliderShape {
+    pub fn collider(&self) -> Collider {
+        match *self {
+            ColliderShape::Cuboid {
+                half_extents: (x, y, z),
+            } => Collider::cuboid(x, y, z),
+            ColliderShape::Ball { radius } => Collider::ball(radius),
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => Collider::capsule_y(half_height, radius),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum LaneSpan {
+    /// sits in a single lane
+    Single,
+    /// centered on the track, covering every lane
+    All,
+}
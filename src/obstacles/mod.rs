@@ -0,0 +1,490 @@
+mod fairness;
+mod manifest;
+mod occupancy;
+mod patterns;
+
+use std::collections::VecDeque;
+
+use crate::{
+    game_state::{GameState, RunAssets},
+    lanes::{LaneChangeConfig, LaneConfig, LaneEntity},
+    player::JumpConfig,
+    ron_loader::RonLoader,
+    seed::RunRng,
+    speed::SpeedCurve,
+    track::{ChunkEntity, JunctionConfig, SpawnChunkEvent, TrackPath, TrackPosition},
+};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Sensor;
+use rand::{distributions::WeightedIndex, prelude::*};
+use serde::Deserialize;
+
+use self::fairness::{any_reachable, repair_row, row_dodges, MoveModel, MAX_ATTEMPTS};
+pub use self::fairness::{Dodge, LaneDodges};
+use self::manifest::{ColliderShape, LaneSpan, ObstacleManifest};
+use self::occupancy::index_obstacles;
+pub use self::occupancy::LaneOccupancy;
+use self::patterns::{plan_pattern_row, ObstaclePatterns};
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ObstacleManifest>()
+            .add_asset_loader(RonLoader::<ObstacleManifest>::new(&["obstacles.ron"]))
+            .add_asset::<ObstaclePatterns>()
+            .add_asset_loader(RonLoader::<ObstaclePatterns>::new(&["patterns.ron"]))
+            .add_event::<ObstacleRowEvent>()
+            .init_resource::<ObstacleResources>()
+            .init_resource::<ObstacleStream>()
+            .init_resource::<LaneOccupancy>()
+            .add_startup_system(setup)
+            .add_system(build_obstacle_resources)
+            .add_system(reset_obstacle_stream.in_schedule(OnEnter(GameState::Loading)))
+            .add_system(spawn_obstacle_chunks)
+            .add_system(index_obstacles);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ObstacleResource {
+    pub obstacle_type: ObstacleType,
+    pub scene_handle: Handle<Scene>,
+    pub collider: ColliderShape,
+    pub collision_offset: Vec3,
+    pub lane_span: LaneSpan,
+    pub spawn_weight: f32,
+    pub min_count: u32,
+    pub max_count: u32,
+}
+
+/// Obstacles built from the manifest, rebuilt whenever the manifest is hot reloaded.
+#[derive(Resource, Default)]
+pub struct ObstacleResources(pub Vec<ObstacleResource>);
+
+#[derive(Resource)]
+struct ObstacleManifestHandle(Handle<ObstacleManifest>);
+
+/// Patterns are read straight from the asset so hot reloaded edits show up on the next pick.
+#[derive(Resource)]
+struct ObstaclePatternsHandle(Handle<ObstaclePatterns>);
+
+/// Track distance of the next obstacle row, rows carry on from one chunk into the next.
+#[derive(Resource)]
+struct ObstacleStream {
+    next_row: f32,
+    last_row: f32,
+    /// rows placed by a pattern that haven't been reached by a chunk yet
+    pending: VecDeque<PendingRow>,
+    /// lanes the player can be in when passing the last row
+    reachable: LaneDodges,
+}
+
+impl ObstacleStream {
+    fn new(lane_config: &LaneConfig) -> Self {
+        // runs start in the middle lane
+        let mut reachable = vec![None; lane_config.count];
+        reachable[lane_config.middle()] = Some(Dodge::Run);
+        Self {
+            next_row: 0.0,
+            last_row: 0.0,
+            pending: VecDeque::new(),
+            reachable,
+        }
+    }
+}
+
+impl FromWorld for ObstacleStream {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(world.resource::<LaneConfig>())
+    }
+}
+
+struct PendingRow {
+    distance: f32,
+    /// `None` for a random row, which is only rolled once it's time to spawn it
+    row: Option<PlannedRow>,
+}
+
+/// Sent for every obstacle row spawned so other generators can build around it.
+pub struct ObstacleRowEvent {
+    pub distance: f32,
+    pub chunk: u32,
+    /// lanes the player can get through the row in, and how
+    pub reachable: LaneDodges,
+}
+
+/// A row of obstacles that is generated but not spawned yet.
+pub struct PlannedRow {
+    pub distance: f32,
+    pub obstacles: Vec<PlannedObstacle>,
+}
+
+pub struct PlannedObstacle {
+    /// index into `ObstacleResources`
+    pub resource: usize,
+    /// index into the lanes of the `LaneConfig`
+    pub lane: usize,
+}
+
+#[derive(Component)]
+pub struct Obstacle {
+    pub obstacle_type: ObstacleType,
+    pub lane_span: LaneSpan,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum ObstacleType {
+    Low,
+    High,
+    Full,
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut run_assets: ResMut<RunAssets>,
+) {
+    let manifest: Handle<ObstacleManifest> = asset_server.load("data/base.obstacles.ron");
+    run_assets.0.push(manifest.clone_untyped());
+    commands.insert_resource(ObstacleManifestHandle(manifest));
+
+    let patterns: Handle<ObstaclePatterns> = asset_server.load("data/base.patterns.ron");
+    run_assets.0.push(patterns.clone_untyped());
+    commands.insert_resource(ObstaclePatternsHandle(patterns));
+}
+
+// (re)build the obstacle resources whenever the manifest finishes loading or changes on disk
+fn build_obstacle_resources(
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<ObstacleManifest>>,
+    manifest_handle: Res<ObstacleManifestHandle>,
+    mut manifest_events: EventReader<AssetEvent<ObstacleManifest>>,
+    mut obstacle_resources: ResMut<ObstacleResources>,
+    mut obstacle_stream: ResMut<ObstacleStream>,
+    mut run_assets: ResMut<RunAssets>,
+) {
+    for event in manifest_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != manifest_handle.0 {
+            continue;
+        }
+        let Some(manifest) = manifests.get(handle) else {
+            continue;
+        };
+
+        obstacle_resources.0 = manifest
+            .obstacles
+            .iter()
+            .map(|definition| {
+                let scene_handle: Handle<Scene> = asset_server.load(definition.model.as_str());
+                // reloads load the same scenes again, only track each one once
+                if !run_assets
+                    .0
+                    .iter()
+                    .any(|asset| asset.id() == scene_handle.id())
+                {
+                    run_assets.0.push(scene_handle.clone_untyped());
+                }
+                let (x, y, z) = definition.offset;
+                let (mut min_count, mut max_count) = (definition.min_count, definition.max_count);
+                // a typo here shouldn't take down a running game on reload
+                if min_count > max_count {
+                    warn!(
+                        "{:?} obstacle has min_count {} above max_count {}, swapping them",
+                        definition.obstacle_type, min_count, max_count
+                    );
+                    std::mem::swap(&mut min_count, &mut max_count);
+                }
+                ObstacleResource {
+                    obstacle_type: definition.obstacle_type,
+                    scene_handle,
+                    collider: definition.collider.clone(),
+                    collision_offset: Vec3::new(x, y, z),
+                    lane_span: definition.lane_span,
+                    spawn_weight: definition.spawn_weight,
+                    min_count,
+                    max_count,
+                }
+            })
+            .collect();
+        info!("loaded {} obstacle definitions", obstacle_resources.0.len());
+
+        // queued pattern rows point into the old definitions, plan them again from where they start
+        if let Some(first) = obstacle_stream.pending.front() {
+            obstacle_stream.next_row = first.distance;
+        }
+        obstacle_stream.pending.clear();
+    }
+}
+
+fn reset_obstacle_stream(
+    lane_config: Res<LaneConfig>,
+    mut obstacle_stream: ResMut<ObstacleStream>,
+) {
+    *obstacle_stream = ObstacleStream::new(&lane_config);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_obstacle_chunks(
+    mut commands: Commands,
+    obstacle_resources: Res<ObstacleResources>,
+    obstacle_patterns: Res<Assets<ObstaclePatterns>>,
+    patterns_handle: Res<ObstaclePatternsHandle>,
+    speed_curve: Res<SpeedCurve>,
+    lane_change_config: Res<LaneChangeConfig>,
+    jump_config: Res<JumpConfig>,
+    lane_config: Res<LaneConfig>,
+    track_path: Res<TrackPath>,
+    junction_config: Res<JunctionConfig>,
+    mut obstacle_stream: ResMut<ObstacleStream>,
+    mut run_rng: ResMut<RunRng>,
+    mut spawn_chunk_events: EventReader<SpawnChunkEvent>,
+    mut obstacle_row_events: EventWriter<ObstacleRowEvent>,
+) {
+    let rng = &mut run_rng.0;
+    let obstacle_resources = &obstacle_resources.0;
+    let obstacle_patterns = obstacle_patterns.get(&patterns_handle.0);
+    let move_model = MoveModel::new(&lane_change_config, &jump_config);
+
+    for chunk in spawn_chunk_events.iter() {
+        if obstacle_resources.is_empty() {
+            // the manifest hasn't loaded yet, leave this chunk clear
+            continue;
+        }
+        // leave a gap at the start of the run
+        if obstacle_stream.next_row == 0.0 {
+            obstacle_stream.next_row = speed_curve.obstacle_spacing(0.0);
+        }
+        loop {
+            if obstacle_stream.pending.is_empty() {
+                queue_rows(
+                    &mut obstacle_stream,
+                    rng,
+                    obstacle_resources,
+                    obstacle_patterns,
+                    &speed_curve,
+                    &lane_config,
+                );
+            }
+            let Some(pending) = obstacle_stream.pending.front() else {
+                break;
+            };
+            if pending.distance >= chunk.end {
+                break;
+            }
+            let pending = obstacle_stream.pending.pop_front().unwrap();
+            let distance = pending.distance;
+            // the player has enough to do getting round a corner
+            if track_path.near_junction(distance, junction_config.clearance) {
+                continue;
+            }
+            let time =
+                (distance - obstacle_stream.last_row) / speed_curve.speed_at_distance(distance);
+
+            let mut planned = pending.row.map(|row| {
+                let reachable = move_model.reachable(
+                    &obstacle_stream.reachable,
+                    &row_dodges(&row, obstacle_resources, lane_config.count),
+                    time,
+                );
+                (row, reachable)
+            });
+            // reroll random rows the player can't get through, then give up and clear a lane
+            if planned.is_none() {
+                for _ in 0..MAX_ATTEMPTS {
+                    let Some(row) =
+                        plan_obstacle_row(rng, obstacle_resources, &lane_config, distance)
+                    else {
+                        break;
+                    };
+                    let reachable = move_model.reachable(
+                        &obstacle_stream.reachable,
+                        &row_dodges(&row, obstacle_resources, lane_config.count),
+                        time,
+                    );
+                    let fair = any_reachable(&reachable);
+                    planned = Some((row, reachable));
+                    if fair {
+                        break;
+                    }
+                }
+            }
+            if let Some((mut row, mut reachable)) = planned {
+                if !any_reachable(&reachable) {
+                    repair_row(&mut row, &obstacle_stream.reachable, obstacle_resources);
+                    reachable = move_model.reachable(
+                        &obstacle_stream.reachable,
+                        &row_dodges(&row, obstacle_resources, lane_config.count),
+                        time,
+                    );
+                }
+                spawn_obstacle_row(
+                    &mut commands,
+                    obstacle_resources,
+                    &lane_config,
+                    &track_path,
+                    &row,
+                    chunk.index,
+                );
+                obstacle_row_events.send(ObstacleRowEvent {
+                    distance,
+                    chunk: chunk.index,
+                    reachable: reachable.clone(),
+                });
+                obstacle_stream.reachable = reachable;
+                obstacle_stream.last_row = distance;
+            }
+        }
+    }
+}
+
+// queue up either a whole pattern or a single random row at the next free stretch of track
+fn queue_rows(
+    obstacle_stream: &mut ObstacleStream,
+    rng: &mut impl Rng,
+    obstacle_resources: &[ObstacleResource],
+    obstacle_patterns: Option<&ObstaclePatterns>,
+    speed_curve: &SpeedCurve,
+    lane_config: &LaneConfig,
+) {
+    let start = obstacle_stream.next_row;
+    let pattern = obstacle_patterns.and_then(|patterns| patterns.pick(rng, start));
+    let Some(pattern) = pattern else {
+        obstacle_stream.pending.push_back(PendingRow {
+            distance: start,
+            row: None,
+        });
+        // spawn a row of obstacles every few seconds of running, rows spread out as the player speeds up
+        obstacle_stream.next_row = start + speed_curve.obstacle_spacing(start);
+        return;
+    };
+
+    debug!("placing pattern {:?} at {}m", pattern.name, start);
+    // keep the pattern's timing the same at any speed
+    let stretch = speed_curve.speed_at_distance(start) / speed_curve.base_speed();
+    let mut last = start;
+    for pattern_row in &pattern.rows {
+        let distance = start + pattern_row.offset * stretch;
+        obstacle_stream.pending.push_back(PendingRow {
+            distance,
+            row: Some(plan_pattern_row(
+                rng,
+                obstacle_resources,
+                pattern_row,
+                lane_config,
+                distance,
+            )),
+        });
+        last = last.max(distance);
+    }
+    obstacle_stream.next_row = last + speed_curve.obstacle_spacing(last);
+}
+
+fn plan_obstacle_row(
+    rng: &mut impl Rng,
+    obstacle_resources: &[ObstacleResource],
+    lane_config: &LaneConfig,
+    distance: f32,
+) -> Option<PlannedRow> {
+    // get a weighted random obstacle resource
+    let Ok(weights) = WeightedIndex::new(obstacle_resources.iter().map(|o| o.spawn_weight)) else {
+        warn!("obstacle spawn weights must be positive");
+        return None;
+    };
+    let resource = weights.sample(rng);
+    let obstacle_resource = &obstacle_resources[resource];
+
+    // get all possibilities of lanes as an array
+    let mut lanes: Vec<usize> = (0..lane_config.count).collect();
+
+    // obstacles covering every lane only need spawning once
+    let obstacle_count = match obstacle_resource.lane_span {
+        LaneSpan::Single => rng
+            .gen_range(obstacle_resource.min_count..=obstacle_resource.max_count)
+            .clamp(1, lanes.len() as u32),
+        LaneSpan::All => 1,
+    };
+
+    let mut obstacles = Vec::new();
+    // iterate on the obstacle count
+    for _ in 0..obstacle_count {
+        let lane_index = rng.gen_range(0..lanes.len());
+        // get a random lane, obstacles covering every lane sit in the middle
+        let lane = match obstacle_resource.lane_span {
+            LaneSpan::Single => lanes[lane_index],
+            LaneSpan::All => lane_config.middle(),
+        };
+        obstacles.push(PlannedObstacle { resource, lane });
+
+        // remove the lane from the possibilities
+        lanes.remove(lane_index);
+    }
+
+    Some(PlannedRow {
+        distance,
+        obstacles,
+    })
+}
+
+fn spawn_obstacle_row(
+    commands: &mut Commands,
+    obstacle_resources: &[ObstacleResource],
+    lane_config: &LaneConfig,
+    track_path: &TrackPath,
+    row: &PlannedRow,
+    chunk: u32,
+) {
+    for planned in &row.obstacles {
+        let obstacle_resource = &obstacle_resources[planned.resource];
+        let lane = planned.lane;
+
+        // lane center, or the track center stretched across every lane
+        let (lane_offset, x_scale) = match obstacle_resource.lane_span {
+            LaneSpan::Single => (lane_config.x(lane), 1.0),
+            LaneSpan::All => (0.0, lane_config.model_scale()),
+        };
+
+        let obstacle_name = format!(
+            "obstacle_{}_{:?}_{:?}",
+            row.distance as i32, obstacle_resource.obstacle_type, lane
+        );
+        let track_position = TrackPosition {
+            distance: row.distance as f64,
+            lane_offset,
+            height: 0.0,
+        };
+        // spawn the obstacle
+        commands
+            .spawn((
+                SceneBundle {
+                    scene: obstacle_resource.scene_handle.clone(),
+                    transform: track_path
+                        .transform(&track_position)
+                        .with_scale(Vec3::new(x_scale, 1.0, 1.0)),
+                    ..default()
+                },
+                Obstacle {
+                    obstacle_type: obstacle_resource.obstacle_type,
+                    lane_span: obstacle_resource.lane_span,
+                },
+                LaneEntity { lane },
+                track_position,
+                ChunkEntity { chunk },
+                Name::new(obstacle_name),
+            ))
+            .with_children(|obstacle| {
+                obstacle.spawn((
+                    TransformBundle::from(Transform::from_translation(
+                        obstacle_resource.collision_offset,
+                    )),
+                    obstacle_resource.collider.collider(),
+                    Sensor,
+                    Name::new("obstacle collider"),
+                ));
+            });
+    }
+}
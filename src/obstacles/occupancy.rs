@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+use crate::{
+    lanes::{LaneConfig, LaneEntity},
+    track::TrackPosition,
+};
+use bevy::prelude::*;
+
+use super::{manifest::LaneSpan, Obstacle, ObstacleType};
+
+/// An obstacle as the occupancy index sees it.
+#[derive(Clone, Debug)]
+pub struct OccupancyEntry {
+    pub entity: Entity,
+    /// track distance of the obstacle
+    pub distance: f32,
+    pub obstacle_type: ObstacleType,
+    /// every lane the obstacle blocks
+    pub lanes: Range<usize>,
+}
+
+/// Obstacles on the track indexed by lane and sorted by distance, kept in sync as they spawn
+/// and despawn.
+#[derive(Resource, Default, Debug)]
+pub struct LaneOccupancy {
+    lanes: Vec<Vec<OccupancyEntry>>,
+}
+
+impl LaneOccupancy {
+    fn insert(&mut self, entry: OccupancyEntry) {
+        if self.lanes.len() < entry.lanes.end {
+            self.lanes.resize_with(entry.lanes.end, Vec::new);
+        }
+        for lane in entry.lanes.clone() {
+            let entries = &mut self.lanes[lane];
+            let index = entries.partition_point(|other| other.distance <= entry.distance);
+            entries.insert(index, entry.clone());
+        }
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        for entries in self.lanes.iter_mut() {
+            entries.retain(|entry| entry.entity != entity);
+        }
+    }
+
+    fn lane(&self, lane: usize) -> &[OccupancyEntry] {
+        self.lanes.get(lane).map_or(&[], Vec::as_slice)
+    }
+
+    /// Obstacles in `lane` with a distance inside `range`, nearest first.
+    pub fn in_lane(
+        &self,
+        lane: usize,
+        range: Range<f32>,
+    ) -> impl Iterator<Item = &OccupancyEntry> + '_ {
+        let entries = self.lane(lane);
+        let start = entries.partition_point(|entry| entry.distance < range.start);
+        entries[start..]
+            .iter()
+            .take_while(move |entry| entry.distance < range.end)
+    }
+
+    /// The first obstacle in `lane` past `distance`.
+    #[allow(dead_code)]
+    pub fn next_in_lane(&self, lane: usize, distance: f32) -> Option<&OccupancyEntry> {
+        let entries = self.lane(lane);
+        entries.get(entries.partition_point(|entry| entry.distance <= distance))
+    }
+
+    /// The first obstacle of `obstacle_type` in `lane` past `distance`.
+    #[allow(dead_code)]
+    pub fn next_of_type(
+        &self,
+        lane: usize,
+        distance: f32,
+        obstacle_type: ObstacleType,
+    ) -> Option<&OccupancyEntry> {
+        self.in_lane(lane, distance..f32::INFINITY)
+            .find(|entry| entry.distance > distance && entry.obstacle_type == obstacle_type)
+    }
+
+    /// Every obstacle with a distance inside `range`, once each even if it spans several lanes.
+    pub fn between(&self, range: Range<f32>) -> impl Iterator<Item = &OccupancyEntry> + '_ {
+        (0..self.lanes.len()).flat_map(move |lane| {
+            self.in_lane(lane, range.clone())
+                .filter(move |entry| entry.lanes.start == lane)
+        })
+    }
+}
+
+pub fn index_obstacles(
+    lane_config: Res<LaneConfig>,
+    mut occupancy: ResMut<LaneOccupancy>,
+    spawned: Query<(Entity, &Obstacle, &LaneEntity, &TrackPosition), Added<Obstacle>>,
+    mut despawned: RemovedComponents<Obstacle>,
+) {
+    for entity in despawned.iter() {
+        occupancy.remove(entity);
+    }
+    for (entity, obstacle, lane_entity, track_position) in spawned.iter() {
+        occupancy.insert(OccupancyEntry {
+            entity,
+            distance: track_position.distance as f32,
+            obstacle_type: obstacle.obstacle_type,
+            lanes: match obstacle.lane_span {
+                LaneSpan::Single => lane_entity.lane..lane_entity.lane + 1,
+                LaneSpan::All => 0..lane_config.count,
+            },
+        });
+    }
+}
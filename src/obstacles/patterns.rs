@@ -0,0 +1,100 @@
+use bevy::reflect::TypeUuid;
+use rand::{distributions::WeightedIndex, prelude::*};
+use serde::Deserialize;
+
+use super::{ObstacleResource, ObstacleType, PlannedObstacle, PlannedRow};
+use crate::lanes::LaneConfig;
+
+/// Hand authored multi-row obstacle layouts, loaded from a `*.patterns.ron` file.
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "b7d3a1e4-2c59-4e8f-8a61-0f4c9d2e7b35"]
+pub struct ObstaclePatterns {
+    /// chance of placing a plain random row instead of a pattern
+    pub random_row_chance: f32,
+    /// track distance each difficulty tier after tier 0 unlocks at
+    pub tier_distances: Vec<f32>,
+    pub patterns: Vec<ObstaclePattern>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ObstaclePattern {
+    pub name: String,
+    /// the pattern shows up once the run reaches this difficulty tier
+    pub tier: u32,
+    /// relative chance of this pattern being picked among the unlocked ones
+    pub weight: f32,
+    pub rows: Vec<PatternRow>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatternRow {
+    /// meters from the first row at base speed, stretched as the run speeds up
+    pub offset: f32,
+    pub obstacles: Vec<PatternObstacle>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatternObstacle {
+    pub obstacle_type: ObstacleType,
+    /// lanes right of the middle lane, negative to the left. ignored for obstacles covering every lane
+    pub lane: i32,
+}
+
+impl ObstaclePatterns {
+    pub fn tier_at(&self, distance: f32) -> u32 {
+        self.tier_distances
+            .iter()
+            .filter(|tier_distance| **tier_distance <= distance)
+            .count() as u32
+    }
+
+    /// Pick an unlocked pattern for a stretch of track starting at `distance`, `None` for a random row.
+    pub fn pick(&self, rng: &mut impl Rng, distance: f32) -> Option<&ObstaclePattern> {
+        if rng.gen::<f32>() < self.random_row_chance {
+            return None;
+        }
+        let tier = self.tier_at(distance);
+        let unlocked: Vec<&ObstaclePattern> = self
+            .patterns
+            .iter()
+            .filter(|pattern| pattern.tier <= tier && !pattern.rows.is_empty())
+            .collect();
+        let weights = WeightedIndex::new(unlocked.iter().map(|pattern| pattern.weight)).ok()?;
+        Some(unlocked[weights.sample(rng)])
+    }
+}
+
+/// Fill in a pattern row with obstacles from the manifest matching each obstacle type.
+pub fn plan_pattern_row(
+    rng: &mut impl Rng,
+    obstacle_resources: &[ObstacleResource],
+    pattern_row: &PatternRow,
+    lane_config: &LaneConfig,
+    distance: f32,
+) -> PlannedRow {
+    let obstacles = pattern_row
+        .obstacles
+        .iter()
+        .filter_map(|pattern_obstacle| {
+            // patterns are authored around the middle, lanes past the edge of a narrow track are dropped
+            let lane = lane_config.offset_from_middle(pattern_obstacle.lane)?;
+            let matching: Vec<usize> = obstacle_resources
+                .iter()
+                .enumerate()
+                .filter(|(_, resource)| resource.obstacle_type == pattern_obstacle.obstacle_type)
+                .map(|(index, _)| index)
+                .collect();
+            let weights =
+                WeightedIndex::new(matching.iter().map(|i| obstacle_resources[*i].spawn_weight))
+                    .ok()?;
+            Some(PlannedObstacle {
+                resource: matching[weights.sample(rng)],
+                lane,
+            })
+        })
+        .collect();
+    PlannedRow {
+        distance,
+        obstacles,
+    }
+}
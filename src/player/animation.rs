@@ -0,0 +1,229 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{game_state::GameState, health::Staggering};
+use bevy::{gltf::Gltf, prelude::*};
+use serde::Deserialize;
+
+use super::{
+    motion::{MotionState, VerticalMotion},
+    Player,
+};
+
+pub struct PlayerAnimationPlugin;
+
+impl Plugin for PlayerAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationGraph>()
+            .init_resource::<PlayerClips>()
+            .add_systems((resolve_clips, update_anim_state, play_anim_state).chain())
+            .add_system(pause_animation.in_schedule(OnEnter(GameState::Paused)))
+            .add_system(resume_animation.in_schedule(OnExit(GameState::Paused)));
+    }
+}
+
+/// Animation the player is in, each one plays a clip from the player glTF.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default, Deserialize)]
+pub enum AnimState {
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Slide,
+    Stumble,
+}
+
+/// Which animation the player is in, readable by anything that cares.
+#[derive(Component, Debug, Default)]
+pub struct PlayerAnimState {
+    pub current: AnimState,
+    pub previous: Option<AnimState>,
+    /// what the animation player was last told to play
+    playing: Option<AnimState>,
+    /// the animation player it was told, swapping characters spawns a new one
+    animator: Option<Entity>,
+}
+
+impl PlayerAnimState {
+    fn set(&mut self, next: AnimState) {
+        if self.current != next {
+            self.previous = Some(self.current);
+            self.current = next;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimClip {
+    /// animation name in the glTF
+    pub name: String,
+    pub repeat: bool,
+}
+
+/// Blend time used going into `to`, from `from` or from anything if `from` is None.
+#[derive(Debug, Clone, Copy)]
+pub struct AnimTransition {
+    pub from: Option<AnimState>,
+    pub to: AnimState,
+    pub blend: f32,
+}
+
+#[derive(Resource, Debug)]
+pub struct AnimationGraph {
+    pub clips: HashMap<AnimState, AnimClip>,
+    pub transitions: Vec<AnimTransition>,
+    pub default_blend: f32,
+}
+
+impl Default for AnimationGraph {
+    fn default() -> Self {
+        let clip = |name: &str, repeat| AnimClip {
+            name: name.to_string(),
+            repeat,
+        };
+        let transition = |from, to, blend| AnimTransition { from, to, blend };
+        Self {
+            clips: HashMap::from([
+                (AnimState::Idle, clip("Idle", true)),
+                (AnimState::Run, clip("Run", true)),
+                (AnimState::Jump, clip("Jump", false)),
+                (AnimState::Slide, clip("Slide", false)),
+                (AnimState::Stumble, clip("Stumble", false)),
+            ]),
+            // snap into moves so they read on time, ease back out of them
+            transitions: vec![
+                transition(None, AnimState::Jump, 0.1),
+                transition(None, AnimState::Slide, 0.1),
+                transition(Some(AnimState::Jump), AnimState::Slide, 0.05),
+                transition(Some(AnimState::Jump), AnimState::Run, 0.15),
+                transition(Some(AnimState::Slide), AnimState::Run, 0.2),
+                transition(None, AnimState::Stumble, 0.05),
+            ],
+            default_blend: 0.25,
+        }
+    }
+}
+
+impl AnimationGraph {
+    pub fn blend(&self, from: AnimState, to: AnimState) -> f32 {
+        self.transitions
+            .iter()
+            .find(|transition| transition.to == to && transition.from == Some(from))
+            .or_else(|| {
+                self.transitions
+                    .iter()
+                    .find(|transition| transition.to == to && transition.from.is_none())
+            })
+            .map_or(self.default_blend, |transition| transition.blend)
+    }
+}
+
+/// Clips of the selected character's glTF, looked up by the names in the graph.
+#[derive(Resource, Default)]
+pub struct PlayerClips {
+    gltf: Option<Handle<Gltf>>,
+    clips: HashMap<AnimState, Handle<AnimationClip>>,
+    resolved: bool,
+}
+
+impl PlayerClips {
+    pub fn use_gltf(&mut self, gltf: Handle<Gltf>) {
+        self.gltf = Some(gltf);
+        self.resolved = false;
+    }
+}
+
+// look the clips up by name once the glTF is in
+fn resolve_clips(
+    graph: Res<AnimationGraph>,
+    gltfs: Res<Assets<Gltf>>,
+    mut player_clips: ResMut<PlayerClips>,
+) {
+    if player_clips.resolved && !graph.is_changed() {
+        return;
+    }
+    let Some(gltf) = player_clips.gltf.as_ref().and_then(|gltf| gltfs.get(gltf)) else {
+        return;
+    };
+    let mut clips = HashMap::new();
+    for (state, clip) in graph.clips.iter() {
+        match gltf.named_animations.get(&clip.name) {
+            Some(handle) => {
+                clips.insert(*state, handle.clone());
+            }
+            // a state without a clip just keeps playing whatever came before it
+            None => warn!(
+                "player has no animation named {} for {:?}",
+                clip.name, state
+            ),
+        }
+    }
+    player_clips.clips = clips;
+    player_clips.resolved = true;
+}
+
+fn update_anim_state(
+    state: Res<State<GameState>>,
+    mut player: Query<(&VerticalMotion, Option<&Staggering>, &mut PlayerAnimState), With<Player>>,
+) {
+    for (motion, staggering, mut anim_state) in player.iter_mut() {
+        let next = match state.0 {
+            GameState::Playing if staggering.is_some() => AnimState::Stumble,
+            GameState::Playing => match motion.state {
+                MotionState::Running => AnimState::Run,
+                MotionState::Rising | MotionState::Apex | MotionState::Falling => AnimState::Jump,
+                MotionState::Sliding => AnimState::Slide,
+            },
+            // hold whatever was playing, the clip itself is paused
+            GameState::Paused => anim_state.current,
+            GameState::MainMenu | GameState::Loading | GameState::GameOver => AnimState::Idle,
+        };
+        anim_state.set(next);
+    }
+}
+
+fn play_anim_state(
+    graph: Res<AnimationGraph>,
+    player_clips: Res<PlayerClips>,
+    mut player: Query<&mut PlayerAnimState, With<Player>>,
+    mut animation_player: Query<(Entity, &mut AnimationPlayer)>,
+) {
+    // the scene spawns its animation player a few frames after the player itself
+    let (Ok(mut anim_state), Ok((animator, mut animation_player))) =
+        (player.get_single_mut(), animation_player.get_single_mut())
+    else {
+        return;
+    };
+    if anim_state.animator != Some(animator) {
+        anim_state.animator = Some(animator);
+        anim_state.playing = None;
+    }
+    if anim_state.playing == Some(anim_state.current) {
+        return;
+    }
+    let (Some(clip), Some(handle)) = (
+        graph.clips.get(&anim_state.current),
+        player_clips.clips.get(&anim_state.current),
+    ) else {
+        return;
+    };
+    let blend = anim_state
+        .playing
+        .map_or(0.0, |playing| graph.blend(playing, anim_state.current));
+    animation_player.play_with_transition(handle.clone_weak(), Duration::from_secs_f32(blend));
+    if clip.repeat {
+        animation_player.repeat();
+    }
+    anim_state.playing = Some(anim_state.current);
+}
+
+fn pause_animation(mut player: Query<&mut AnimationPlayer>) {
+    if let Ok(mut player) = player.get_single_mut() {
+        player.pause();
+    }
+}
+
+fn resume_animation(mut player: Query<&mut AnimationPlayer>) {
+    if let Ok(mut player) = player.get_single_mut() {
+        player.resume();
+    }
+}
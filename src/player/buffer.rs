@@ -0,0 +1,140 @@
+use crate::input::{ActionState, InputAction};
+use bevy::prelude::*;
+
+use super::{
+    motion::{JumpConfig, MotionState, VerticalMotion},
+    Player,
+};
+
+const MOVE_ACTIONS: [InputAction; 4] = [
+    InputAction::MoveLeft,
+    InputAction::MoveRight,
+    InputAction::Jump,
+    InputAction::Slide,
+];
+
+/// Which actions may cut into a move that is still playing, anything else waits in the buffer.
+#[derive(Resource, Debug)]
+pub struct ActionRules {
+    /// how long a pressed action stays queued before it is dropped
+    pub buffer_window: f32,
+    pub max_buffered: usize,
+    /// (move in progress, action) pairs that interrupt the move instead of waiting for it
+    pub cancels: Vec<(MotionState, InputAction)>,
+    /// downward speed a slide forces on a jump it cancels
+    pub fast_fall_velocity: f32,
+}
+
+impl Default for ActionRules {
+    fn default() -> Self {
+        let mut cancels = vec![
+            (MotionState::Sliding, InputAction::Jump),
+            // slide out of the air into a fast-fall, the slide starts on landing
+            (MotionState::Rising, InputAction::Slide),
+            (MotionState::Apex, InputAction::Slide),
+            (MotionState::Falling, InputAction::Slide),
+        ];
+        // changing lanes mid jump or slide is part of the feel, take these out to queue them instead
+        for state in [
+            MotionState::Rising,
+            MotionState::Apex,
+            MotionState::Falling,
+            MotionState::Sliding,
+        ] {
+            cancels.push((state, InputAction::MoveLeft));
+            cancels.push((state, InputAction::MoveRight));
+        }
+        Self {
+            buffer_window: 0.2,
+            max_buffered: 2,
+            cancels,
+            fast_fall_velocity: 40.0,
+        }
+    }
+}
+
+impl ActionRules {
+    /// Whether `action` can run right now while the player is in `state`.
+    pub fn allows(&self, state: MotionState, action: InputAction) -> bool {
+        state == MotionState::Running || self.cancels.contains(&(state, action))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BufferedAction {
+    action: InputAction,
+    age: f32,
+}
+
+/// Move actions pressed too early to run yet, oldest first.
+#[derive(Component, Debug, Default)]
+pub struct ActionBuffer {
+    queued: Vec<BufferedAction>,
+    /// lanes to move this frame, picked up by move_player
+    pub lane_change: i32,
+}
+
+impl ActionBuffer {
+    pub fn clear(&mut self) {
+        self.queued.clear();
+        self.lane_change = 0;
+    }
+}
+
+pub fn run_buffered_actions(
+    action_state: Res<ActionState>,
+    action_rules: Res<ActionRules>,
+    jump_config: Res<JumpConfig>,
+    mut player: Query<(&mut ActionBuffer, &mut VerticalMotion), With<Player>>,
+    time: Res<Time>,
+) {
+    for (mut buffer, mut motion) in player.iter_mut() {
+        buffer.lane_change = 0;
+        for queued in buffer.queued.iter_mut() {
+            queued.age += time.delta_seconds();
+        }
+        buffer
+            .queued
+            .retain(|queued| queued.age <= action_rules.buffer_window);
+        for action in MOVE_ACTIONS {
+            if action_state.just_pressed(action) {
+                buffer.queued.push(BufferedAction { action, age: 0.0 });
+            }
+        }
+        // mashing shouldn't pile up a backlog, keep the newest presses
+        let overflow = buffer
+            .queued
+            .len()
+            .saturating_sub(action_rules.max_buffered);
+        buffer.queued.drain(..overflow);
+
+        // run whatever the current move allows in press order, the rest keeps waiting
+        let mut waiting = Vec::new();
+        for queued in std::mem::take(&mut buffer.queued) {
+            if !action_rules.allows(motion.state, queued.action) {
+                waiting.push(queued);
+                continue;
+            }
+            match queued.action {
+                InputAction::MoveLeft => buffer.lane_change -= 1,
+                InputAction::MoveRight => buffer.lane_change += 1,
+                InputAction::Jump => {
+                    motion.state = MotionState::Rising;
+                    motion.velocity = jump_config.jump_velocity;
+                    motion.slide_on_landing = false;
+                }
+                InputAction::Slide if motion.state.airborne() => {
+                    motion.state = MotionState::Falling;
+                    motion.velocity = motion.velocity.min(-action_rules.fast_fall_velocity);
+                    motion.slide_on_landing = true;
+                }
+                InputAction::Slide => {
+                    motion.state = MotionState::Sliding;
+                    motion.timer = jump_config.slide_duration;
+                }
+                _ => {}
+            }
+        }
+        buffer.queued = waiting;
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use crate::{
+    game_state::{GameState, RunAssets},
+    input::{ActionState, InputAction},
+    ron_loader::RonLoader,
+};
+use bevy::{gltf::Gltf, prelude::*, reflect::TypeUuid};
+use bevy_rapier3d::prelude::Collider;
+use serde::Deserialize;
+
+use super::{
+    animation::{AnimState, AnimationGraph, PlayerClips},
+    motion::PlayerColliders,
+    Player, PlayerCollider,
+};
+
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<CharacterRoster>()
+            .add_asset_loader(RonLoader::<CharacterRoster>::new(&["characters.ron"]))
+            .init_resource::<SelectedCharacter>()
+            .init_resource::<CharacterAssets>()
+            .add_startup_system(setup)
+            .add_system(select_character.in_set(OnUpdate(GameState::MainMenu)))
+            .add_system(load_characters)
+            .add_system(apply_character.after(load_characters));
+    }
+}
+
+/// Every playable character, loaded from a `*.characters.ron` file.
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "9c2a6f3e-1d84-4b7a-8e05-3f6b9d2c7a41"]
+pub struct CharacterRoster {
+    pub characters: Vec<CharacterDefinition>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CharacterDefinition {
+    pub name: String,
+    /// glTF path relative to assets/, the first scene in it is spawned
+    pub model: String,
+    /// animation names in the glTF for each state
+    pub clips: HashMap<AnimState, String>,
+    pub colliders: PlayerColliders,
+    /// degrees around y to turn the model so it runs down the track
+    pub rotation: f32,
+}
+
+/// Roster index of the character the next run uses.
+#[derive(Resource, Default)]
+pub struct SelectedCharacter(pub usize);
+
+/// How the selected character's model sits on the player.
+#[derive(Component, Default)]
+pub struct CharacterModel {
+    /// radians around y
+    pub rotation: f32,
+}
+
+#[derive(Resource)]
+struct CharacterRosterHandle(Handle<CharacterRoster>);
+
+/// Handles of every character in the roster, in roster order.
+#[derive(Resource, Default)]
+struct CharacterAssets {
+    gltfs: Vec<Handle<Gltf>>,
+    scenes: Vec<Handle<Scene>>,
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut run_assets: ResMut<RunAssets>,
+) {
+    let roster: Handle<CharacterRoster> = asset_server.load("data/base.characters.ron");
+    run_assets.0.push(roster.clone_untyped());
+    commands.insert_resource(CharacterRosterHandle(roster));
+}
+
+// load every character up front so picking one in the menu doesn't wait on a model
+fn load_characters(
+    asset_server: Res<AssetServer>,
+    rosters: Res<Assets<CharacterRoster>>,
+    roster_handle: Res<CharacterRosterHandle>,
+    mut roster_events: EventReader<AssetEvent<CharacterRoster>>,
+    mut character_assets: ResMut<CharacterAssets>,
+    mut selected: ResMut<SelectedCharacter>,
+    mut run_assets: ResMut<RunAssets>,
+) {
+    for event in roster_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != roster_handle.0 {
+            continue;
+        }
+        let Some(roster) = rosters.get(handle) else {
+            continue;
+        };
+
+        character_assets.gltfs.clear();
+        character_assets.scenes.clear();
+        for character in roster.characters.iter() {
+            let gltf: Handle<Gltf> = asset_server.load(character.model.as_str());
+            let scene: Handle<Scene> = asset_server.load(format!("{}#Scene0", character.model));
+            run_assets.0.push(gltf.clone_untyped());
+            run_assets.0.push(scene.clone_untyped());
+            character_assets.gltfs.push(gltf);
+            character_assets.scenes.push(scene);
+        }
+        if selected.0 >= roster.characters.len() {
+            selected.0 = 0;
+        } else {
+            // the definition may have changed on disk, apply it again
+            selected.set_changed();
+        }
+        info!("loaded {} characters", roster.characters.len());
+    }
+}
+
+fn select_character(
+    action_state: Res<ActionState>,
+    rosters: Res<Assets<CharacterRoster>>,
+    roster_handle: Res<CharacterRosterHandle>,
+    mut selected: ResMut<SelectedCharacter>,
+) {
+    let Some(roster) = rosters.get(&roster_handle.0) else {
+        return;
+    };
+    let count = roster.characters.len();
+    if count == 0 {
+        return;
+    }
+    let step = action_state.just_pressed(InputAction::MoveRight) as i32
+        - action_state.just_pressed(InputAction::MoveLeft) as i32;
+    if step != 0 {
+        selected.0 = (selected.0 as i32 + step).rem_euclid(count as i32) as usize;
+        info!("selected {}", roster.characters[selected.0].name);
+    }
+}
+
+// swap the player over to the selected character's model, clips and colliders
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_character(
+    mut commands: Commands,
+    rosters: Res<Assets<CharacterRoster>>,
+    roster_handle: Res<CharacterRosterHandle>,
+    character_assets: Res<CharacterAssets>,
+    selected: Res<SelectedCharacter>,
+    mut player_colliders: ResMut<PlayerColliders>,
+    mut graph: ResMut<AnimationGraph>,
+    mut player_clips: ResMut<PlayerClips>,
+    mut player: Query<(Entity, &mut Transform, &mut CharacterModel), With<Player>>,
+    mut player_collision: Query<
+        (&mut Transform, &mut Collider),
+        (With<PlayerCollider>, Without<Player>),
+    >,
+) {
+    if !selected.is_changed() {
+        return;
+    }
+    let Some(character) = rosters
+        .get(&roster_handle.0)
+        .and_then(|roster| roster.characters.get(selected.0))
+    else {
+        return;
+    };
+    let (Some(gltf), Some(scene)) = (
+        character_assets.gltfs.get(selected.0),
+        character_assets.scenes.get(selected.0),
+    ) else {
+        return;
+    };
+
+    for (entity, mut transform, mut model) in player.iter_mut() {
+        model.rotation = character.rotation.to_radians();
+        transform.rotation = Quat::from_rotation_y(model.rotation);
+        // the scene spawner swaps the old model out when the handle changes
+        commands.entity(entity).insert(scene.clone());
+    }
+
+    *player_colliders = character.colliders.clone();
+    for (mut transform, mut collider) in player_collision.iter_mut() {
+        transform.translation.y = player_colliders.standing.center_y;
+        *collider = player_colliders.standing.collider();
+    }
+
+    for (state, name) in character.clips.iter() {
+        if let Some(clip) = graph.clips.get_mut(state) {
+            clip.name = name.clone();
+        }
+    }
+    player_clips.use_gltf(gltf.clone());
+}
@@ -0,0 +1,369 @@
+use crate::{
+    game_state::GameState,
+    health::{DamageConfig, Health, HitOutcome, Invulnerable, PlayerHitEvent, Staggering},
+    lanes::{LaneChangeConfig, LaneConfig, LaneEntity, LaneTween},
+    obstacles::Obstacle,
+    score::RunStats,
+    speed::RunSpeed,
+    track::{ApplyTrackPositions, TrackPosition},
+};
+use bevy::prelude::*;
+use bevy_rapier3d::{parry, prelude::*, rapier::math::Isometry};
+
+use self::{
+    animation::PlayerAnimationPlugin,
+    buffer::{run_buffered_actions, ActionBuffer, ActionRules},
+    character::{CharacterModel, CharacterPlugin},
+    motion::{fit_collider_to_motion, update_vertical_motion, PlayerColliders, VerticalMotion},
+};
+
+mod animation;
+mod buffer;
+mod character;
+mod motion;
+
+pub use animation::{AnimState, PlayerAnimState};
+pub use motion::JumpConfig;
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JumpConfig>()
+            .init_resource::<PlayerColliders>()
+            .init_resource::<ActionRules>()
+            .add_plugin(PlayerAnimationPlugin)
+            .add_plugin(CharacterPlugin)
+            .add_startup_system(setup)
+            .add_systems(
+                (
+                    run_buffered_actions,
+                    update_vertical_motion,
+                    fit_collider_to_motion,
+                )
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_systems(
+                (
+                    move_player.after(run_buffered_actions),
+                    move_player_root.before(ApplyTrackPositions),
+                    handle_collision_events,
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(reset_player.in_schedule(OnEnter(GameState::Loading)));
+    }
+}
+
+#[derive(Component)]
+pub struct Player;
+
+#[derive(Component)]
+pub struct PlayerCollider;
+
+#[derive(Component)]
+pub struct PlayerRoot;
+
+const CAMERA_HEIGHT: f32 = 10.0;
+const CAM_Z_DISTANCE: f32 = 10.0;
+
+fn setup(
+    mut commands: Commands,
+    lane_config: Res<LaneConfig>,
+    player_colliders: Res<PlayerColliders>,
+) {
+    let start_lane = lane_config.middle();
+    commands
+        .spawn((
+            SceneBundle {
+                transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+                ..default()
+            },
+            TrackPosition::default(),
+            PlayerRoot,
+            Name::new("player root"),
+        ))
+        .with_children(|root| {
+            // the model scene is added once a character is picked from the roster
+            root.spawn((
+                SpatialBundle::default(),
+                Name::new("player"),
+                Player,
+                CharacterModel::default(),
+                LaneEntity { lane: start_lane },
+                LaneTween::at(lane_config.x(start_lane)),
+                Health::default(),
+                VerticalMotion::default(),
+                ActionBuffer::default(),
+                PlayerAnimState::default(),
+            ))
+            .with_children(|player| {
+                player.spawn((
+                    TransformBundle::from(Transform::from_translation(Vec3::new(
+                        0.0,
+                        player_colliders.standing.center_y,
+                        0.0,
+                    ))),
+                    player_colliders.standing.collider(),
+                    Sensor,
+                    Name::new("player collider"),
+                    PlayerCollider,
+                    RigidBody::Dynamic,
+                    GravityScale(0.0), // jumps are integrated in motion.rs
+                    ActiveEvents::COLLISION_EVENTS,
+                ));
+            });
+            root.spawn(Camera3dBundle {
+                transform: Transform::from_xyz(0.0, CAMERA_HEIGHT, CAM_Z_DISTANCE)
+                    .looking_at(Vec3::Y * CAMERA_HEIGHT / 1.5, Vec3::Y),
+                ..default()
+            });
+        });
+}
+
+// put the player back at the start of the track for a new run
+#[allow(clippy::type_complexity)]
+fn reset_player(
+    lane_config: Res<LaneConfig>,
+    player_colliders: Res<PlayerColliders>,
+    mut player_root: Query<&mut TrackPosition, With<PlayerRoot>>,
+    mut player: Query<
+        (
+            &mut Transform,
+            &mut LaneEntity,
+            &mut LaneTween,
+            &mut VerticalMotion,
+            &mut ActionBuffer,
+            &CharacterModel,
+        ),
+        (With<Player>, Without<PlayerRoot>, Without<PlayerCollider>),
+    >,
+    mut player_collision: Query<
+        (&mut Transform, &mut Collider),
+        (With<PlayerCollider>, Without<PlayerRoot>, Without<Player>),
+    >,
+) {
+    for mut player_root_position in player_root.iter_mut() {
+        *player_root_position = TrackPosition::default();
+    }
+    for (mut player_transform, mut lane_entity, mut lane_tween, mut motion, mut buffer, model) in
+        player.iter_mut()
+    {
+        let start_x = lane_config.x(lane_config.middle());
+        lane_entity.lane = lane_config.middle();
+        buffer.clear();
+        *lane_tween = LaneTween::at(start_x);
+        *motion = VerticalMotion::default();
+        player_transform.translation.x = start_x;
+        player_transform.translation.y = 0.0;
+        player_transform.rotation = Quat::from_rotation_y(model.rotation);
+    }
+    for (mut transform, mut collider) in player_collision.iter_mut() {
+        transform.translation.y = player_colliders.standing.center_y;
+        *collider = player_colliders.standing.collider();
+    }
+}
+
+fn move_player_root(
+    mut player_root: Query<&mut TrackPosition, With<PlayerRoot>>,
+    mut run_stats: ResMut<RunStats>,
+    run_speed: Res<RunSpeed>,
+    time: Res<Time>,
+) {
+    for mut player_root_position in player_root.iter_mut() {
+        let step = time.delta_seconds() * run_speed.speed;
+        player_root_position.distance += step as f64;
+        run_stats.distance += step;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn move_player(
+    lane_change_config: Res<LaneChangeConfig>,
+    lane_config: Res<LaneConfig>,
+    damage_config: Res<DamageConfig>,
+    mut player: Query<
+        (
+            &mut Transform,
+            &mut LaneEntity,
+            &mut LaneTween,
+            &ActionBuffer,
+            &CharacterModel,
+            Option<&Staggering>,
+        ),
+        With<Player>,
+    >,
+    time: Res<Time>,
+) {
+    for (mut player_transform, mut lane_entity, mut lane_tween, buffer, model, staggering) in
+        player.iter_mut()
+    {
+        // move the player by the lane changes the buffer let through this frame
+        let previous_lane = lane_entity.lane;
+        lane_entity.change_lane(buffer.lane_change, &lane_config);
+        if lane_entity.lane != previous_lane {
+            // start from wherever the player is so changing again mid tween stays smooth
+            lane_tween.start(
+                player_transform.translation.x,
+                lane_config.x(lane_entity.lane),
+                lane_change_config.duration,
+            );
+        }
+
+        // ease x position toward the lane, the collider is a child so it follows along
+        lane_tween.elapsed += time.delta_seconds();
+        player_transform.translation.x = lane_tween.x(lane_change_config.easing);
+
+        // roll into the lane change, peaking halfway
+        let progress = lane_tween.progress();
+        let lean = if progress < 1.0 {
+            (lane_tween.to - lane_tween.from).signum()
+                * lane_change_config.max_lean
+                * (std::f32::consts::PI * progress).sin()
+        } else {
+            0.0
+        };
+        // lurch back and recover over a stumble, characters don't need a clip for it
+        let pitch = staggering.map_or(0.0, |staggering| {
+            -damage_config.stagger_pitch * (std::f32::consts::PI * staggering.timer.percent()).sin()
+        });
+        player_transform.rotation = Quat::from_rotation_y(model.rotation)
+            * Quat::from_rotation_x(pitch)
+            * Quat::from_rotation_z(lean);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_collision_events(
+    mut commands: Commands,
+    query_player_collider: Query<Entity, With<PlayerCollider>>,
+    mut query_player: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Health,
+            &mut LaneEntity,
+            &mut LaneTween,
+            Option<&Invulnerable>,
+        ),
+        With<Player>,
+    >,
+    query_player_root: Query<&GlobalTransform, With<PlayerRoot>>,
+    query_colliders: Query<(&GlobalTransform, &Collider)>,
+    query_parents: Query<&Parent>,
+    query_obstacles: Query<&Obstacle>,
+    damage_config: Res<DamageConfig>,
+    lane_config: Res<LaneConfig>,
+    lane_change_config: Res<LaneChangeConfig>,
+    mut contact_events: EventReader<CollisionEvent>,
+    mut hit_events: EventWriter<PlayerHitEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok((player, player_transform, mut health, mut lane_entity, mut lane_tween, invulnerable)) =
+        query_player.get_single_mut()
+    else {
+        return;
+    };
+    // which way is sideways depends on where the track has carried the player
+    let track_rotation = query_player_root
+        .get_single()
+        .map_or(Quat::IDENTITY, |root| {
+            root.to_scale_rotation_translation().1
+        });
+    // the invulnerable marker only lands once commands are applied, so only take one hit per frame
+    let mut hit_taken = invulnerable.is_some();
+    for contact_event in contact_events.iter() {
+        for player_entity in query_player_collider.iter() {
+            if let CollisionEvent::Started(h1, h2, _event_flag) = contact_event {
+                if (h1 == &player_entity || h2 == &player_entity) && !hit_taken {
+                    let other = if h1 == &player_entity { h2 } else { h1 };
+                    // obstacle colliders are children of the obstacle scene, anything else isn't a hit
+                    let Some(obstacle_type) = query_parents
+                        .get(*other)
+                        .ok()
+                        .and_then(|parent| query_obstacles.get(parent.get()).ok())
+                        .map(|obstacle| obstacle.obstacle_type)
+                    else {
+                        continue;
+                    };
+
+                    let outcome = if is_side_hit(
+                        &query_colliders,
+                        player_entity,
+                        *other,
+                        &lane_tween,
+                        track_rotation,
+                    ) {
+                        // bounce back to the lane the change started from
+                        lane_entity.lane = lane_config.nearest(lane_tween.from);
+                        lane_tween.start(
+                            player_transform.translation.x,
+                            lane_config.x(lane_entity.lane),
+                            lane_change_config.duration,
+                        );
+                        commands
+                            .entity(player)
+                            .insert(Staggering::new(damage_config.stagger_duration));
+                        HitOutcome::SideHit
+                    } else {
+                        health.take_hit(damage_config.mode)
+                    };
+                    match outcome {
+                        HitOutcome::Stumble => {
+                            commands
+                                .entity(player)
+                                .insert(Invulnerable::new(damage_config.invulnerability_duration));
+                        }
+                        HitOutcome::Fatal => next_state.set(GameState::GameOver),
+                        HitOutcome::SideHit => {}
+                    }
+                    hit_events.send(PlayerHitEvent {
+                        outcome,
+                        obstacle_type,
+                        lives_left: health.lives,
+                    });
+                    hit_taken = true;
+                }
+            }
+        }
+    }
+}
+
+// a side hit is the player running into an obstacle sideways partway through a lane change,
+// sensors don't get contact manifolds so ask parry for the contact normal directly
+fn is_side_hit(
+    query_colliders: &Query<(&GlobalTransform, &Collider)>,
+    player_collider: Entity,
+    obstacle_collider: Entity,
+    lane_tween: &LaneTween,
+    track_rotation: Quat,
+) -> bool {
+    if lane_tween.progress() >= 1.0 {
+        return false;
+    }
+    let (Ok((player_transform, player_shape)), Ok((obstacle_transform, obstacle_shape))) = (
+        query_colliders.get(player_collider),
+        query_colliders.get(obstacle_collider),
+    ) else {
+        return false;
+    };
+    let isometry = |transform: &GlobalTransform| {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        Isometry::from_parts(translation.into(), rotation.into())
+    };
+    let Ok(Some(contact)) = parry::query::contact(
+        &isometry(player_transform),
+        player_shape.raw.as_ref(),
+        &isometry(obstacle_transform),
+        obstacle_shape.raw.as_ref(),
+        0.0,
+    ) else {
+        return false;
+    };
+    // the normal points from the player toward the obstacle, it has to be the way the player is moving.
+    // parry gives it in world space, lanes run across the track
+    let normal = track_rotation.inverse() * Vec3::from(contact.normal1.into_inner());
+    let moving = (lane_tween.to - lane_tween.from).signum();
+    normal.x.abs() > normal.z.abs() && normal.x.abs() > normal.y.abs() && normal.x * moving > 0.0
+}
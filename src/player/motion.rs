@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use super::{Player, PlayerCollider};
+
+/// Where the player is in a jump or slide, animations follow this rather than the other way round.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum MotionState {
+    #[default]
+    Running,
+    Rising,
+    Apex,
+    Falling,
+    Sliding,
+}
+
+impl MotionState {
+    pub fn airborne(self) -> bool {
+        matches!(self, Self::Rising | Self::Apex | Self::Falling)
+    }
+}
+
+/// Vertical motion of the player, integrated by hand since the player collider is a sensor.
+#[derive(Component, Debug, Default)]
+pub struct VerticalMotion {
+    pub state: MotionState,
+    /// height above the player root
+    pub height: f32,
+    pub velocity: f32,
+    /// time left at the apex or in the slide
+    pub timer: f32,
+    /// a slide cancelled the jump, start sliding as soon as the feet touch down
+    pub slide_on_landing: bool,
+}
+
+#[derive(Resource, Debug)]
+pub struct JumpConfig {
+    /// upward velocity the jump starts with
+    pub jump_velocity: f32,
+    pub gravity: f32,
+    /// rising slower than this counts as reaching the apex
+    pub apex_velocity: f32,
+    /// how long the jump floats at the top before falling
+    pub apex_hang_time: f32,
+    /// gravity multiplier while hanging at the apex
+    pub apex_gravity_scale: f32,
+    pub slide_duration: f32,
+    /// how far below the feet to look for ground
+    pub ground_probe: f32,
+}
+
+impl Default for JumpConfig {
+    fn default() -> Self {
+        // 0.4s up, 0.1s hang, 0.4s down, about 3 units high
+        Self {
+            jump_velocity: 15.0,
+            gravity: 37.5,
+            apex_velocity: 1.0,
+            apex_hang_time: 0.1,
+            apex_gravity_scale: 0.2,
+            slide_duration: 0.9,
+            ground_probe: 50.0,
+        }
+    }
+}
+
+impl JumpConfig {
+    /// Seconds from leaving the ground until landing back at the same height.
+    pub fn air_time(&self) -> f32 {
+        let rise = ((self.jump_velocity - self.apex_velocity) / self.gravity).max(0.0);
+        let rise_height = (self.jump_velocity.powi(2) - self.apex_velocity.powi(2)).max(0.0)
+            / (2.0 * self.gravity);
+        let hang = self.apex_hang_time;
+        let hang_gravity = self.gravity * self.apex_gravity_scale;
+        let hang_height = self.apex_velocity * hang - 0.5 * hang_gravity * hang * hang;
+        let velocity = self.apex_velocity - hang_gravity * hang;
+        let height = (rise_height + hang_height).max(0.0);
+        let fall =
+            (velocity + (velocity * velocity + 2.0 * self.gravity * height).sqrt()) / self.gravity;
+        rise + hang + fall
+    }
+}
+
+/// Box the player collider takes while standing or sliding, relative to the player.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ColliderBox {
+    pub half_extents: (f32, f32, f32),
+    pub center_y: f32,
+}
+
+impl ColliderBox {
+    pub fn collider(&self) -> Collider {
+        let (x, y, z) = self.half_extents;
+        Collider::cuboid(x, y, z)
+    }
+}
+
+/// Collider boxes of the selected character, set from the roster.
+#[derive(Resource, Debug, Clone, Deserialize)]
+pub struct PlayerColliders {
+    pub standing: ColliderBox,
+    pub sliding: ColliderBox,
+}
+
+impl Default for PlayerColliders {
+    fn default() -> Self {
+        Self {
+            standing: ColliderBox {
+                half_extents: (1.0, 3.52, 1.0),
+                center_y: 4.3,
+            },
+            sliding: ColliderBox {
+                half_extents: (1.0, 1.76, 1.0),
+                center_y: 2.15,
+            },
+        }
+    }
+}
+
+impl PlayerColliders {
+    pub fn for_state(&self, state: MotionState) -> &ColliderBox {
+        match state {
+            MotionState::Sliding => &self.sliding,
+            _ => &self.standing,
+        }
+    }
+}
+
+pub fn update_vertical_motion(
+    jump_config: Res<JumpConfig>,
+    rapier_context: Res<RapierContext>,
+    mut player: Query<(&GlobalTransform, &mut Transform, &mut VerticalMotion), With<Player>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (global_transform, mut transform, mut motion) in player.iter_mut() {
+        match motion.state {
+            MotionState::Running => {}
+            MotionState::Sliding => {
+                motion.timer -= dt;
+                if motion.timer <= 0.0 {
+                    motion.state = MotionState::Running;
+                }
+            }
+            MotionState::Rising => {
+                motion.velocity -= jump_config.gravity * dt;
+                if motion.velocity <= jump_config.apex_velocity {
+                    motion.state = MotionState::Apex;
+                    motion.timer = jump_config.apex_hang_time;
+                }
+            }
+            MotionState::Apex => {
+                motion.velocity -= jump_config.gravity * jump_config.apex_gravity_scale * dt;
+                motion.timer -= dt;
+                if motion.timer <= 0.0 {
+                    motion.state = MotionState::Falling;
+                }
+            }
+            MotionState::Falling => {
+                motion.velocity -= jump_config.gravity * dt;
+            }
+        }
+        motion.height += motion.velocity * dt;
+
+        if motion.state.airborne() && motion.velocity <= 0.0 {
+            // look for the boardwalk under the feet, sensors are obstacles and coins so skip them
+            let root_y = global_transform.translation().y - transform.translation.y;
+            let origin = Vec3::new(
+                global_transform.translation().x,
+                root_y + motion.height + jump_config.ground_probe,
+                global_transform.translation().z,
+            );
+            let ground = rapier_context
+                .cast_ray(
+                    origin,
+                    Vec3::NEG_Y,
+                    jump_config.ground_probe * 2.0,
+                    true,
+                    QueryFilter::new().exclude_sensors(),
+                )
+                .map(|(_, toi)| origin.y - toi - root_y)
+                // no boardwalk streamed in under the player yet, land on the track height
+                .unwrap_or(0.0);
+            if motion.height <= ground {
+                motion.height = ground;
+                motion.velocity = 0.0;
+                if std::mem::take(&mut motion.slide_on_landing) {
+                    motion.state = MotionState::Sliding;
+                    motion.timer = jump_config.slide_duration;
+                } else {
+                    motion.state = MotionState::Running;
+                }
+            }
+        }
+
+        transform.translation.y = motion.height;
+    }
+}
+
+// swap the collider for a shorter one while sliding, it follows jumps as a child of the player
+pub fn fit_collider_to_motion(
+    player_colliders: Res<PlayerColliders>,
+    player: Query<&VerticalMotion, (With<Player>, Changed<VerticalMotion>)>,
+    mut player_collision: Query<(&mut Transform, &mut Collider), With<PlayerCollider>>,
+) {
+    let Ok(motion) = player.get_single() else {
+        return;
+    };
+    let target = player_colliders.for_state(motion.state);
+    for (mut transform, mut collider) in player_collision.iter_mut() {
+        if transform.translation.y != target.center_y {
+            transform.translation.y = target.center_y;
+            *collider = target.collider();
+        }
+    }
+}
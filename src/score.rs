@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::{
+    game_state::GameState,
+    health::{HitOutcome, PlayerHitEvent},
+    obstacles::{LaneOccupancy, ObstacleType},
+    player::PlayerRoot,
+    track::{TrackPath, TrackPosition, Turn, TurnEvent},
+};
+use bevy::prelude::*;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<PassedCheck>()
+            .add_system(reset_run_stats.in_schedule(OnEnter(GameState::Loading)))
+            .add_systems(
+                (
+                    count_passed_obstacles,
+                    count_stumbles,
+                    count_turns,
+                    count_laps,
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(finish_run_stats.in_schedule(OnEnter(GameState::GameOver)));
+    }
+}
+
+/// Progress of the current run, reset whenever a new run starts loading.
+#[derive(Resource, Default, Debug)]
+pub struct RunStats {
+    /// total distance travelled in meters
+    pub distance: f32,
+    pub obstacles_passed: HashMap<ObstacleType, u32>,
+    /// hits the player survived
+    pub stumbles: u32,
+    /// times the player bounced off the side of an obstacle
+    pub side_hits: u32,
+    /// value of the coins picked up
+    pub coins: u32,
+    /// way the player went at every junction, in order
+    pub turns: Vec<Turn>,
+    /// times the player ran the whole authored track
+    pub laps: u32,
+    /// only set once the run is over
+    pub final_score: Option<u32>,
+}
+
+impl RunStats {
+    pub fn obstacles_passed_total(&self) -> u32 {
+        self.obstacles_passed.values().sum()
+    }
+
+    /// one point per meter plus a bonus for every obstacle passed and coin picked up,
+    /// minus a penalty for every side hit
+    pub fn score(&self) -> u32 {
+        let obstacle_points: u32 = self
+            .obstacles_passed
+            .iter()
+            .map(|(obstacle_type, count)| obstacle_points(*obstacle_type) * count)
+            .sum();
+        (self.distance as u32 + obstacle_points + self.coins * COIN_POINTS)
+            .saturating_sub(self.side_hits * SIDE_HIT_PENALTY)
+    }
+}
+
+const COIN_POINTS: u32 = 5;
+const SIDE_HIT_PENALTY: u32 = 25;
+
+fn obstacle_points(obstacle_type: ObstacleType) -> u32 {
+    match obstacle_type {
+        ObstacleType::Low => 10,
+        ObstacleType::High => 10,
+        ObstacleType::Full => 5,
+    }
+}
+
+/// Track distance obstacles were already counted up to.
+#[derive(Resource, Default)]
+struct PassedCheck {
+    counted_to: f32,
+}
+
+fn reset_run_stats(mut run_stats: ResMut<RunStats>, mut passed_check: ResMut<PassedCheck>) {
+    *run_stats = RunStats::default();
+    *passed_check = PassedCheck::default();
+}
+
+fn count_passed_obstacles(
+    mut run_stats: ResMut<RunStats>,
+    mut passed_check: ResMut<PassedCheck>,
+    occupancy: Res<LaneOccupancy>,
+    player_root: Query<&TrackPosition, With<PlayerRoot>>,
+) {
+    let Ok(player_root_position) = player_root.get_single() else {
+        return;
+    };
+    let distance = player_root_position.distance as f32;
+    for entry in occupancy.between(passed_check.counted_to..distance) {
+        *run_stats
+            .obstacles_passed
+            .entry(entry.obstacle_type)
+            .or_insert(0) += 1;
+    }
+    passed_check.counted_to = passed_check.counted_to.max(distance);
+}
+
+fn count_stumbles(mut run_stats: ResMut<RunStats>, mut hit_events: EventReader<PlayerHitEvent>) {
+    for hit in hit_events.iter() {
+        match hit.outcome {
+            HitOutcome::Stumble => run_stats.stumbles += 1,
+            HitOutcome::SideHit => run_stats.side_hits += 1,
+            HitOutcome::Fatal => {}
+        }
+    }
+}
+
+fn count_turns(mut run_stats: ResMut<RunStats>, mut turn_events: EventReader<TurnEvent>) {
+    for turn in turn_events.iter() {
+        if turn.choice {
+            info!("took the {:?} branch at {:.0}m", turn.turn, turn.distance);
+        }
+        run_stats.turns.push(turn.turn);
+    }
+}
+
+fn count_laps(
+    mut run_stats: ResMut<RunStats>,
+    track_path: Res<TrackPath>,
+    player_root: Query<&TrackPosition, With<PlayerRoot>>,
+) {
+    let Ok(player_root_position) = player_root.get_single() else {
+        return;
+    };
+    let laps = track_path.lap(player_root_position.distance as f32);
+    if laps > run_stats.laps {
+        run_stats.laps = laps;
+        info!("lap {} done", laps);
+    }
+}
+
+fn finish_run_stats(mut run_stats: ResMut<RunStats>) {
+    let score = run_stats.score();
+    run_stats.final_score = Some(score);
+    info!(
+        "run over: {:.0}m, {} obstacles passed, {} coins, {} stumbles, {} side hits, turns {:?}, {} laps, score {}",
+        run_stats.distance,
+        run_stats.obstacles_passed_total(),
+        run_stats.coins,
+        run_stats.stumbles,
+        run_stats.side_hits,
+        run_stats.turns,
+        run_stats.laps,
+        score
+    );
+}
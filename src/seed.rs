@@ -0,0 +1,70 @@
+use std::env;
+
+use crate::game_state::GameState;
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub struct SeedPlugin;
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        let run_seed = RunSeed::from_args();
+        app.insert_resource(RunRng(StdRng::seed_from_u64(run_seed.seed)))
+            .insert_resource(run_seed)
+            .add_system(reseed_run.in_schedule(OnEnter(GameState::Loading)))
+            .add_system(show_seed.in_schedule(OnEnter(GameState::GameOver)));
+    }
+}
+
+/// Seed of the current run, the same seed always generates the same track.
+#[derive(Resource, Debug)]
+pub struct RunSeed {
+    pub seed: u64,
+    /// set from the command line or environment, otherwise every run rolls a new seed
+    pub fixed: bool,
+}
+
+impl RunSeed {
+    /// Reads `--seed <n>` from the command line, falling back to the `GOON_SEED` environment variable.
+    fn from_args() -> Self {
+        let args: Vec<String> = env::args().collect();
+        let seed = args
+            .iter()
+            .position(|arg| arg == "--seed")
+            .and_then(|i| args.get(i + 1).cloned())
+            .or_else(|| env::var("GOON_SEED").ok())
+            .and_then(|seed| match seed.parse() {
+                Ok(seed) => Some(seed),
+                Err(_) => {
+                    warn!("ignoring invalid seed {:?}", seed);
+                    None
+                }
+            });
+        match seed {
+            Some(seed) => Self { seed, fixed: true },
+            None => Self {
+                seed: rand::thread_rng().gen(),
+                fixed: false,
+            },
+        }
+    }
+}
+
+/// Rng every bit of track generation has to draw from so runs stay reproducible.
+#[derive(Resource)]
+pub struct RunRng(pub StdRng);
+
+fn reseed_run(mut run_seed: ResMut<RunSeed>, mut run_rng: ResMut<RunRng>) {
+    if !run_seed.fixed {
+        run_seed.seed = rand::thread_rng().gen();
+    }
+    run_rng.0 = StdRng::seed_from_u64(run_seed.seed);
+    info!("run seed {}", run_seed.seed);
+}
+
+fn show_seed(run_seed: Res<RunSeed>) {
+    info!(
+        "run seed was {}, replay it with --seed {}",
+        run_seed.seed, run_seed.seed
+    );
+}
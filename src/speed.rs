@@ -0,0 +1,134 @@
+use crate::{
+    game_state::GameState,
+    player::{AnimState, PlayerAnimState},
+    score::RunStats,
+};
+use bevy::prelude::*;
+
+pub struct SpeedPlugin;
+
+impl Plugin for SpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedCurve>()
+            .init_resource::<RunSpeed>()
+            .add_startup_system(check_speed_curve)
+            .add_system(reset_run_speed.in_schedule(OnEnter(GameState::Loading)))
+            .add_systems(
+                (update_run_speed, scale_animation_speed)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(reset_animation_speed.in_schedule(OnEnter(GameState::GameOver)));
+    }
+}
+
+/// Piecewise linear curve from distance run to run speed, capped at `max_speed`.
+#[derive(Resource, Clone, Debug)]
+pub struct SpeedCurve {
+    /// (distance, speed) pairs sorted by distance
+    pub points: Vec<(f32, f32)>,
+    pub max_speed: f32,
+    /// seconds the player should get between obstacle rows, spacing grows with speed to keep it
+    pub reaction_time: f32,
+}
+
+impl Default for SpeedCurve {
+    fn default() -> Self {
+        Self {
+            points: vec![(0.0, 20.0), (1000.0, 28.0), (3000.0, 38.0), (6000.0, 45.0)],
+            max_speed: 45.0,
+            reaction_time: 2.5,
+        }
+    }
+}
+
+impl SpeedCurve {
+    pub fn base_speed(&self) -> f32 {
+        self.sample(0.0)
+    }
+
+    pub fn sample(&self, x: f32) -> f32 {
+        let speed = match self.points.iter().position(|(px, _)| *px > x) {
+            Some(0) => self.points[0].1,
+            Some(i) => {
+                let (x0, y0) = self.points[i - 1];
+                let (x1, y1) = self.points[i];
+                y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+            }
+            None => self.points.last().map_or(self.max_speed, |(_, y)| *y),
+        };
+        speed.min(self.max_speed)
+    }
+
+    /// The speed the player will be running at once they reach `distance`.
+    pub fn speed_at_distance(&self, distance: f32) -> f32 {
+        self.sample(distance)
+    }
+
+    /// Everything downstream divides by the speed, so a curve has to keep the player moving.
+    fn is_valid(&self) -> bool {
+        !self.points.is_empty()
+            && self.max_speed > 0.0
+            && self.points.iter().all(|(_, speed)| *speed > 0.0)
+    }
+
+    /// Distance to leave before the next obstacle row placed at `distance`.
+    pub fn obstacle_spacing(&self, distance: f32) -> f32 {
+        self.speed_at_distance(distance) * self.reaction_time
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct RunSpeed {
+    /// meters per second the player root moves down the track
+    pub speed: f32,
+}
+
+fn check_speed_curve(mut speed_curve: ResMut<SpeedCurve>) {
+    if !speed_curve.is_valid() {
+        warn!(
+            "speed curve {:?} doesn't keep the player moving, using the default",
+            *speed_curve
+        );
+        *speed_curve = SpeedCurve::default();
+    }
+}
+
+fn reset_run_speed(mut run_speed: ResMut<RunSpeed>, speed_curve: Res<SpeedCurve>) {
+    *run_speed = RunSpeed {
+        speed: speed_curve.base_speed(),
+    };
+}
+
+fn update_run_speed(
+    mut run_speed: ResMut<RunSpeed>,
+    speed_curve: Res<SpeedCurve>,
+    run_stats: Res<RunStats>,
+) {
+    run_speed.speed = speed_curve.sample(run_stats.distance);
+}
+
+// keep the run cycle in step with the ground speed, jumps and slides are timed by their motion
+fn scale_animation_speed(
+    run_speed: Res<RunSpeed>,
+    speed_curve: Res<SpeedCurve>,
+    anim_state: Query<&PlayerAnimState>,
+    mut animation_player: Query<&mut AnimationPlayer>,
+) {
+    let (Ok(anim_state), Ok(mut player)) =
+        (anim_state.get_single(), animation_player.get_single_mut())
+    else {
+        return;
+    };
+    let speed = match anim_state.current {
+        AnimState::Run => run_speed.speed / speed_curve.base_speed(),
+        _ => 1.0,
+    };
+    player.set_speed(speed);
+}
+
+fn reset_animation_speed(mut animation_player: Query<&mut AnimationPlayer>) {
+    if let Ok(mut player) = animation_player.get_single_mut() {
+        player.set_speed(1.0);
+    }
+}
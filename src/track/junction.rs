@@ -0,0 +1,209 @@
+use std::{f32::consts::FRAC_PI_2, ops::Range};
+
+use crate::{
+    game_state::GameState,
+    input::{ActionState, InputAction},
+    lanes::LaneConfig,
+    player::PlayerRoot,
+};
+use bevy::{
+    math::{DQuat, DVec3},
+    prelude::*,
+};
+use rand::Rng;
+
+use super::{ApplyTrackPositions, TrackPath, TrackPosition};
+
+pub struct JunctionPlugin;
+
+impl Plugin for JunctionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JunctionConfig>()
+            .add_event::<TurnEvent>()
+            .add_system(
+                take_turns
+                    .in_set(OnUpdate(GameState::Playing))
+                    .before(ApplyTrackPositions),
+            );
+    }
+}
+
+/// Designer facing settings for where junctions go and how strict turning into them is.
+#[derive(Resource, Debug)]
+pub struct JunctionConfig {
+    /// track distance the first junction can show up at
+    pub first: f32,
+    /// meters between one junction and the next, picked at random
+    pub spacing: Range<f32>,
+    /// chance a junction is a T with a branch each way instead of a plain corner
+    pub choice_chance: f64,
+    /// how far before a junction a turn press counts
+    pub window: f32,
+    /// how far into the corner the player can still turn before they run off the edge
+    pub late_grace: f32,
+    /// how far the inside of a corner clears the outer lane
+    pub corner_margin: f32,
+    /// track kept clear of obstacles either side of a corner
+    pub clearance: f32,
+}
+
+impl Default for JunctionConfig {
+    fn default() -> Self {
+        Self {
+            first: 800.0,
+            spacing: 600.0..1200.0,
+            choice_chance: 0.3,
+            window: 20.0,
+            late_grace: 4.0,
+            corner_margin: 2.0,
+            clearance: 30.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Turn {
+    /// Which way around y the track turns.
+    fn sign(self) -> f32 {
+        match self {
+            Turn::Left => 1.0,
+            Turn::Right => -1.0,
+        }
+    }
+
+    /// Actions that take this turn inside the window. Moving a lane counts too, so a swipe is
+    /// all a touch player needs at a corner.
+    fn actions(self) -> [InputAction; 2] {
+        match self {
+            Turn::Left => [InputAction::TurnLeft, InputAction::MoveLeft],
+            Turn::Right => [InputAction::TurnRight, InputAction::MoveRight],
+        }
+    }
+}
+
+/// A 90 degree corner in the track, or a T the player picks a branch of.
+#[derive(Clone, Debug)]
+pub struct Junction {
+    /// track distance the corner starts at
+    pub distance: f32,
+    /// ways the track can go from here, two for a T
+    pub branches: Vec<Turn>,
+    /// the branch the player turned into
+    pub taken: Option<Turn>,
+    /// radius the track center turns around
+    pub radius: f32,
+}
+
+impl Junction {
+    /// Branch the track follows, a plain corner goes its only way before the player turns.
+    pub fn turn(&self) -> Option<Turn> {
+        self.taken.or(match self.branches[..] {
+            [turn] => Some(turn),
+            _ => None,
+        })
+    }
+
+    /// Track distance the corner takes up.
+    pub fn arc_length(&self) -> f32 {
+        self.radius * FRAC_PI_2
+    }
+
+    /// A T nobody picked a branch of yet, the track can't stream past it.
+    pub fn awaiting_choice(&self) -> bool {
+        self.turn().is_none()
+    }
+
+    /// Point `along` meters into the corner turning `turn` from a corner starting at `start`.
+    pub(super) fn corner(
+        &self,
+        turn: Turn,
+        (start, rotation): (DVec3, DQuat),
+        along: f64,
+    ) -> (DVec3, DQuat) {
+        let sign = turn.sign() as f64;
+        let radius = self.radius as f64;
+        let angle = DQuat::from_rotation_y(sign * along / radius);
+        let pivot = DVec3::new(-sign * radius, 0.0, 0.0);
+        let local = pivot + angle * DVec3::new(sign * radius, 0.0, 0.0);
+        (start + rotation * local, rotation * angle)
+    }
+}
+
+/// Sent when the player turns into a junction.
+pub struct TurnEvent {
+    pub distance: f32,
+    pub turn: Turn,
+    /// the junction was a T and this was the player's pick
+    pub choice: bool,
+}
+
+pub(super) fn plan_junction(
+    rng: &mut impl Rng,
+    junction_config: &JunctionConfig,
+    lane_config: &LaneConfig,
+    distance: f32,
+) -> Junction {
+    let branches = if rng.gen_bool(junction_config.choice_chance.clamp(0.0, 1.0)) {
+        vec![Turn::Left, Turn::Right]
+    } else if rng.gen_bool(0.5) {
+        vec![Turn::Left]
+    } else {
+        vec![Turn::Right]
+    };
+    Junction {
+        distance,
+        branches,
+        taken: None,
+        radius: lane_config.track_width() / 2.0 + junction_config.corner_margin,
+    }
+}
+
+fn take_turns(
+    action_state: Res<ActionState>,
+    junction_config: Res<JunctionConfig>,
+    mut track_path: ResMut<TrackPath>,
+    mut turn_events: EventWriter<TurnEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    player_root: Query<&TrackPosition, With<PlayerRoot>>,
+) {
+    let Ok(player_root_position) = player_root.get_single() else {
+        return;
+    };
+    let distance = player_root_position.distance as f32;
+    let Some((index, junction)) = track_path
+        .junctions()
+        .iter()
+        .enumerate()
+        .find(|(_, junction)| junction.taken.is_none())
+    else {
+        return;
+    };
+    if distance < junction.distance - junction_config.window {
+        return;
+    }
+    if distance > junction.distance + junction_config.late_grace {
+        info!("missed the turn at {:.0}m", junction.distance);
+        next_state.set(GameState::GameOver);
+        return;
+    }
+    let Some(turn) = junction.branches.iter().copied().find(|turn| {
+        turn.actions()
+            .into_iter()
+            .any(|action| action_state.just_pressed(action))
+    }) else {
+        return;
+    };
+    let choice = junction.branches.len() > 1;
+    let junction_distance = junction.distance;
+    track_path.take_turn(index, turn);
+    turn_events.send(TurnEvent {
+        distance: junction_distance,
+        turn,
+        choice,
+    });
+}
@@ -0,0 +1,162 @@
+use crate::{game_state::GameState, lanes::LaneConfig, player::PlayerRoot, seed::RunSeed};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+mod junction;
+mod origin;
+mod path;
+
+pub use junction::{JunctionConfig, Turn, TurnEvent};
+pub use path::TrackPath;
+
+pub struct TrackPlugin;
+
+impl Plugin for TrackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrackStreaming>()
+            .add_event::<SpawnChunkEvent>()
+            .add_plugin(path::TrackPathPlugin)
+            .add_plugin(junction::JunctionPlugin)
+            .add_plugin(origin::FloatingOriginPlugin)
+            .add_system(apply_track_positions.in_set(ApplyTrackPositions))
+            .add_system(reset_track.in_schedule(OnEnter(GameState::Loading)))
+            // wait for the run assets before streaming in a new track
+            .add_system(stream_chunks.run_if(not(in_state(GameState::Loading))));
+    }
+}
+
+/// Where something sits in track space. Its `Transform` is derived from this through the
+/// `TrackPath`, so gameplay code only ever moves things along the track.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TrackPosition {
+    /// meters down the track from the start, f64 so the player still moves smoothly hours in
+    pub distance: f64,
+    /// sideways from the track center, positive to the right
+    pub lane_offset: f32,
+    /// above the track surface
+    pub height: f32,
+}
+
+/// Transforms are derived from track positions in here, anything moving along the track should
+/// run before it so physics sees the new position the same frame.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApplyTrackPositions;
+
+/// Length of a streamed piece of track, a whole number of boardwalk segments.
+pub const CHUNK_LENGTH: f32 = 168.0;
+
+#[derive(Resource)]
+pub struct TrackStreaming {
+    /// chunks kept spawned in front of the player
+    pub chunks_ahead: u32,
+    /// chunks kept spawned behind the player so the camera never sees the track end
+    pub chunks_behind: u32,
+    /// index of the next chunk to spawn
+    next_chunk: u32,
+    /// track distance from which the next chunk boundary gets a junction
+    next_junction: f32,
+}
+
+impl Default for TrackStreaming {
+    fn default() -> Self {
+        Self {
+            chunks_ahead: 6,
+            chunks_behind: 1,
+            next_chunk: 0,
+            next_junction: JunctionConfig::default().first,
+        }
+    }
+}
+
+/// Sent once for every chunk that streams in, each plugin fills in its own part of the track.
+pub struct SpawnChunkEvent {
+    pub index: u32,
+    /// track distance the chunk starts at
+    pub start: f32,
+    /// track distance the chunk ends at
+    pub end: f32,
+}
+
+/// Everything spawned for a chunk, despawned together once the player is far enough past it.
+#[derive(Component)]
+pub struct ChunkEntity {
+    pub chunk: u32,
+}
+
+fn reset_track(
+    mut commands: Commands,
+    junction_config: Res<JunctionConfig>,
+    mut track_streaming: ResMut<TrackStreaming>,
+    mut track_path: ResMut<TrackPath>,
+    chunk_entities: Query<Entity, With<ChunkEntity>>,
+) {
+    for entity in chunk_entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    track_streaming.next_chunk = 0;
+    track_streaming.next_junction = junction_config.first;
+    track_path.clear_junctions();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn stream_chunks(
+    mut commands: Commands,
+    junction_config: Res<JunctionConfig>,
+    lane_config: Res<LaneConfig>,
+    mut track_streaming: ResMut<TrackStreaming>,
+    mut track_path: ResMut<TrackPath>,
+    run_seed: Res<RunSeed>,
+    mut spawn_chunk_events: EventWriter<SpawnChunkEvent>,
+    player_root: Query<&TrackPosition, With<PlayerRoot>>,
+    chunk_entities: Query<(Entity, &ChunkEntity)>,
+) {
+    let Ok(player_root_position) = player_root.get_single() else {
+        return;
+    };
+    let player_chunk = (player_root_position.distance / CHUNK_LENGTH as f64).max(0.0) as u32;
+
+    // spawn ahead
+    while track_streaming.next_chunk <= player_chunk + track_streaming.chunks_ahead {
+        let index = track_streaming.next_chunk;
+        let start = index as f32 * CHUNK_LENGTH;
+        let end = (index + 1) as f32 * CHUNK_LENGTH;
+        // nothing past a T streams in until the player picks a branch
+        if track_path
+            .junction_at(start)
+            .is_some_and(|junction| junction.awaiting_choice())
+        {
+            break;
+        }
+        // junctions go on chunk boundaries so the chunk before can lay out the corner
+        if end >= track_streaming.next_junction {
+            // seeded per chunk so how fast the player turns can't shift the obstacle layout
+            let mut rng = StdRng::seed_from_u64(run_seed.seed ^ (u64::from(index) << 32));
+            let junction = junction::plan_junction(&mut rng, &junction_config, &lane_config, end);
+            track_path.add_junction(junction);
+            track_streaming.next_junction =
+                end + rng.gen_range(junction_config.spacing.start..junction_config.spacing.end);
+        }
+        spawn_chunk_events.send(SpawnChunkEvent { index, start, end });
+        track_streaming.next_chunk += 1;
+    }
+
+    // despawn behind
+    for (entity, chunk_entity) in chunk_entities.iter() {
+        if chunk_entity.chunk + track_streaming.chunks_behind < player_chunk {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn apply_track_positions(
+    track_path: Res<TrackPath>,
+    mut positioned: Query<(Ref<TrackPosition>, &mut Transform)>,
+) {
+    for (position, mut transform) in positioned.iter_mut() {
+        if track_path.is_changed() || position.is_changed() {
+            let derived = track_path.transform(&position);
+            transform.translation = derived.translation;
+            transform.rotation = derived.rotation;
+        }
+    }
+}
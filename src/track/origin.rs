@@ -0,0 +1,82 @@
+use crate::{environment::skybox::Skybox, game_state::GameState, player::PlayerRoot};
+use bevy::prelude::*;
+
+use super::{ApplyTrackPositions, TrackPath, TrackPosition};
+
+pub struct FloatingOriginPlugin;
+
+impl Plugin for FloatingOriginPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FloatingOrigin>()
+            .add_system(reset_origin.in_schedule(OnEnter(GameState::Loading)))
+            // in the same frame as the track positions so physics only ever sees a shifted world
+            .add_system(
+                shift_origin
+                    .in_set(OnUpdate(GameState::Playing))
+                    .before(ApplyTrackPositions),
+            );
+    }
+}
+
+/// Keeps the player near the world origin so positions don't lose precision on long runs.
+#[derive(Resource, Debug)]
+pub struct FloatingOrigin {
+    /// how far the player can get from the origin before the world is moved back
+    pub threshold: f32,
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self { threshold: 1000.0 }
+    }
+}
+
+// the skybox follows the camera on its own
+type Unparented<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Transform,
+    (
+        Without<Parent>,
+        Without<TrackPosition>,
+        Without<Node>,
+        Without<Skybox>,
+    ),
+>;
+
+// put the lights back where a fresh run expects them
+fn reset_origin(mut track_path: ResMut<TrackPath>, mut unparented: Unparented) {
+    let offset = -track_path.origin.as_vec3();
+    if offset != Vec3::ZERO {
+        move_world(offset, &mut track_path, &mut unparented);
+    }
+}
+
+// everything moves by the same offset in the same frame, so nothing moves relative to anything
+// else and neither the camera nor rapier notice
+fn shift_origin(
+    floating_origin: Res<FloatingOrigin>,
+    mut track_path: ResMut<TrackPath>,
+    player_root: Query<&TrackPosition, With<PlayerRoot>>,
+    mut unparented: Unparented,
+) {
+    let Ok(player_root_position) = player_root.get_single() else {
+        return;
+    };
+    let position = track_path.transform(player_root_position).translation;
+    if position.length() < floating_origin.threshold {
+        return;
+    }
+
+    move_world(-position, &mut track_path, &mut unparented);
+}
+
+// track content follows the path's origin, everything else at the top of the hierarchy is
+// moved by hand
+fn move_world(offset: Vec3, track_path: &mut TrackPath, unparented: &mut Unparented) {
+    track_path.origin += offset.as_dvec3();
+    for mut transform in unparented.iter_mut() {
+        transform.translation += offset;
+    }
+    debug!("moved the world origin by {}", offset);
+}
@@ -0,0 +1,306 @@
+use crate::{game_state::RunAssets, ron_loader::RonLoader};
+use bevy::{
+    math::{DQuat, DVec3},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+use super::{
+    junction::{Junction, Turn},
+    TrackPosition,
+};
+
+pub struct TrackPathPlugin;
+
+impl Plugin for TrackPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<TrackDefinition>()
+            .add_asset_loader(RonLoader::<TrackDefinition>::new(&["track.ron"]))
+            .init_resource::<TrackPath>()
+            .add_startup_system(setup)
+            .add_system(bake_track_path);
+    }
+}
+
+/// Meters between baked samples of the path.
+const SAMPLE_SPACING: f32 = 1.0;
+/// Points evaluated on each spline span while measuring its length.
+const SPAN_STEPS: usize = 64;
+
+/// One piece of track as spline control points, loaded from a `*.track.ron` file. The piece
+/// repeats end to end for as long as the run goes.
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "5d1e8b47-92c3-4f06-a7d8-1b3c6e9f2a50"]
+pub struct TrackDefinition {
+    /// world points the track runs through, starting at the origin heading down -z
+    pub points: Vec<(f32, f32, f32)>,
+}
+
+#[derive(Resource)]
+struct TrackDefinitionHandle(Handle<TrackDefinition>);
+
+#[derive(Clone, Copy, Debug)]
+struct PathSample {
+    position: Vec3,
+    rotation: Quat,
+}
+
+/// The shape of the track through the world. Straight down -z until a track definition is
+/// baked into it, the junctions the run streamed in turn everything past them.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct TrackPath {
+    /// world position of the start of the track, far away once the origin has been shifted a few
+    /// times
+    pub origin: DVec3,
+    /// one piece of the track sampled every `SAMPLE_SPACING` meters
+    samples: Vec<PathSample>,
+    /// where the next piece starts relative to the last one, and how far it is turned around y
+    piece_offset: Vec3,
+    piece_turn: Quat,
+    /// sorted by distance
+    junctions: Vec<Junction>,
+}
+
+impl TrackPath {
+    /// Sample the Catmull-Rom spline through `points` by arc length.
+    fn bake(&mut self, points: &[Vec3]) {
+        self.samples.clear();
+        if points.len() < 2 {
+            return;
+        }
+        // leave the first point heading down -z, carry on straight past the last one
+        let last = points.len() - 1;
+        let mut controls = Vec::with_capacity(points.len() + 2);
+        controls.push(points[0] + Vec3::Z * points[0].distance(points[1]));
+        controls.extend_from_slice(points);
+        controls.push(points[last] * 2.0 - points[last - 1]);
+
+        let mut dense = vec![points[0]];
+        for span in controls.windows(4) {
+            for step in 1..=SPAN_STEPS {
+                let t = step as f32 / SPAN_STEPS as f32;
+                dense.push(catmull_rom(span, t));
+            }
+        }
+
+        // walk the polyline, dropping a sample every SAMPLE_SPACING meters along it
+        let mut positions = vec![dense[0]];
+        let mut travelled = 0.0;
+        let mut next = SAMPLE_SPACING;
+        for pair in dense.windows(2) {
+            let length = pair[0].distance(pair[1]);
+            while travelled + length >= next && length > 0.0 {
+                positions.push(pair[0].lerp(pair[1], (next - travelled) / length));
+                next += SAMPLE_SPACING;
+            }
+            travelled += length;
+        }
+        if positions.len() < 2 {
+            return;
+        }
+
+        self.samples = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &position)| {
+                let ahead = positions[(i + 1).min(positions.len() - 1)];
+                let behind = positions[i.saturating_sub(1)];
+                PathSample {
+                    position,
+                    // lanes stay level and perpendicular to the tangent
+                    rotation: Transform::IDENTITY
+                        .looking_to(ahead - behind, Vec3::Y)
+                        .rotation,
+                }
+            })
+            .collect();
+
+        // only the heading carries over, a piece that ends on a slope starts the next one level
+        let end = positions[positions.len() - 1];
+        let heading = end - positions[positions.len() - 2];
+        self.piece_offset = end - positions[0];
+        self.piece_turn = Quat::from_rotation_y((-heading.x).atan2(-heading.z));
+    }
+
+    fn piece_length(&self) -> f32 {
+        (self.samples.len() - 1) as f32 * SAMPLE_SPACING
+    }
+
+    pub fn junctions(&self) -> &[Junction] {
+        &self.junctions
+    }
+
+    pub fn add_junction(&mut self, junction: Junction) {
+        let index = self
+            .junctions
+            .partition_point(|other| other.distance <= junction.distance);
+        self.junctions.insert(index, junction);
+    }
+
+    pub fn clear_junctions(&mut self) {
+        self.junctions.clear();
+    }
+
+    pub fn take_turn(&mut self, index: usize, turn: Turn) {
+        self.junctions[index].taken = Some(turn);
+    }
+
+    /// The path as it would be if the player turned `turn` at the junction starting at
+    /// `distance`, for showing the branches of a T before one is picked.
+    pub fn with_turn(&self, distance: f32, turn: Turn) -> TrackPath {
+        let mut path = self.clone();
+        if let Some(junction) = path.junctions.iter_mut().find(|j| j.distance == distance) {
+            junction.taken = Some(turn);
+        }
+        path
+    }
+
+    /// The junction whose corner starts at `distance`.
+    pub fn junction_at(&self, distance: f32) -> Option<&Junction> {
+        self.junctions.iter().find(|j| j.distance == distance)
+    }
+
+    /// Whether `distance` is within `margin` of a corner.
+    pub fn near_junction(&self, distance: f32, margin: f32) -> bool {
+        self.junctions.iter().any(|junction| {
+            distance > junction.distance - margin
+                && distance < junction.distance + junction.arc_length() + margin
+        })
+    }
+
+    /// How many times the player has run the whole authored piece by `distance`, corners don't
+    /// count towards it.
+    pub fn lap(&self, distance: f32) -> u32 {
+        if self.samples.len() < 2 {
+            return 0;
+        }
+        let corners: f32 = self
+            .junctions
+            .iter()
+            .filter(|junction| junction.turn().is_some() && junction.distance <= distance)
+            .map(|junction| (distance - junction.distance).min(junction.arc_length()))
+            .sum();
+        ((distance - corners).max(0.0) / self.piece_length()) as u32
+    }
+
+    /// Center of the track at `distance` and the rotation facing down it.
+    pub fn frame(&self, distance: f64) -> (Vec3, Quat) {
+        let (position, rotation) = self.frame_f64(distance);
+        (position.as_vec3(), rotation.as_f32())
+    }
+
+    // done in f64, far down the track the shifted origin and the pieces run so far are both huge
+    // and would cancel out into a jittery f32 position
+    fn frame_f64(&self, distance: f64) -> (DVec3, DQuat) {
+        // the base path pauses through each corner, everything past it is carried around it
+        let mut shift = DVec3::ZERO;
+        let mut carry = DQuat::IDENTITY;
+        let mut skipped = 0.0;
+        for junction in &self.junctions {
+            let junction_distance = junction.distance as f64;
+            if distance < junction_distance {
+                break;
+            }
+            let Some(turn) = junction.turn() else {
+                continue;
+            };
+            let (position, rotation) = self.base_frame(junction_distance - skipped);
+            let start = (shift + carry * position, carry * rotation);
+            let arc = junction.arc_length() as f64;
+            let along = distance - junction_distance;
+            let (end, end_rotation) = junction.corner(turn, start, along.min(arc));
+            if along <= arc {
+                return (end, end_rotation);
+            }
+            let around = end_rotation * start.1.inverse();
+            shift = end + around * (shift - start.0);
+            carry = around * carry;
+            skipped += arc;
+        }
+        let (position, rotation) = self.base_frame(distance - skipped);
+        (shift + carry * position, carry * rotation)
+    }
+
+    fn base_frame(&self, distance: f64) -> (DVec3, DQuat) {
+        if self.samples.len() < 2 || distance < 0.0 {
+            return (self.origin + DVec3::NEG_Z * distance, DQuat::IDENTITY);
+        }
+
+        let piece_length = self.piece_length() as f64;
+        let pieces = (distance / piece_length).floor();
+        let mut start = self.origin;
+        let mut turn = DQuat::IDENTITY;
+        let piece_offset = self.piece_offset.as_dvec3();
+        let piece_turn = self.piece_turn.as_f64();
+        for _ in 0..pieces as u32 {
+            start += turn * piece_offset;
+            turn *= piece_turn;
+        }
+
+        let along = (distance - pieces * piece_length) / SAMPLE_SPACING as f64;
+        let index = (along as usize).min(self.samples.len() - 2);
+        let t = (along - index as f64) as f32;
+        let (a, b) = (self.samples[index], self.samples[index + 1]);
+        (
+            start + turn * (a.position.lerp(b.position, t) - self.samples[0].position).as_dvec3(),
+            turn * a.rotation.slerp(b.rotation, t).as_f64(),
+        )
+    }
+
+    pub fn transform(&self, position: &TrackPosition) -> Transform {
+        let (center, rotation) = self.frame(position.distance);
+        Transform::from_translation(
+            center + rotation * Vec3::new(position.lane_offset, position.height, 0.0),
+        )
+        .with_rotation(rotation)
+    }
+}
+
+fn catmull_rom(span: &[Vec3], t: f32) -> Vec3 {
+    let (p0, p1, p2, p3) = (span[0], span[1], span[2], span[3]);
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut run_assets: ResMut<RunAssets>,
+) {
+    let track: Handle<TrackDefinition> = asset_server.load("data/base.track.ron");
+    run_assets.0.push(track.clone_untyped());
+    commands.insert_resource(TrackDefinitionHandle(track));
+}
+
+// rebaking marks the path changed, which moves everything on the track onto the new shape
+fn bake_track_path(
+    definitions: Res<Assets<TrackDefinition>>,
+    definition_handle: Res<TrackDefinitionHandle>,
+    mut definition_events: EventReader<AssetEvent<TrackDefinition>>,
+    mut track_path: ResMut<TrackPath>,
+) {
+    for event in definition_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != definition_handle.0 {
+            continue;
+        }
+        let Some(definition) = definitions.get(handle) else {
+            continue;
+        };
+        let points: Vec<Vec3> = definition.points.iter().map(|&p| p.into()).collect();
+        track_path.bake(&points);
+        info!(
+            "baked a {:.0}m track piece from {} points",
+            track_path.samples.len().saturating_sub(1) as f32 * SAMPLE_SPACING,
+            points.len()
+        );
+    }
+}